@@ -2,7 +2,7 @@ use steam_tradeoffer_manager::TradeOfferManager;
 use steam_tradeoffer_manager::response::{TradeOffer, Asset};
 use steam_tradeoffer_manager::enums::TradeOfferState;
 use steam_tradeoffer_manager::error::Error;
-use steam_tradeoffer_manager::polling::PollOptions;
+use steam_tradeoffer_manager::polling::{PollOptions, PollEvent, CancelPolicy};
 use chrono::Duration;
 use owo_colors::OwoColorize;
 
@@ -68,31 +68,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Fails if you did not set your cookies.
     let (_tx, mut rx) = manager.start_polling(PollOptions {
-        // By default PollOptions does not have a cancel duration.
-        cancel_duration: Some(Duration::try_minutes(30).unwrap()),
+        // By default PollOptions does not cancel offers.
+        cancel_policy: CancelPolicy::with_duration(Duration::try_minutes(30).unwrap()),
         ..PollOptions::default()
     })?;
     
     // Listen to the receiver for events.
     while let Some(message) = rx.recv().await {
         match message {
-            Ok(offers) => {
-                for (mut offer, old_state) in offers {
-                    if let Some(state) = old_state {
-                        println!(
-                            "{} Offer changed state: {state} -> {}",
-                            offer.bright_magenta().bold(),
-                            offer.trade_offer_state,
-                        );
-                    }
-                    
-                    // Skip offers that are ours.
-                    if offer.is_our_offer {
-                        continue;
-                    }
+            Ok(events) => {
+                for event in events {
+                    match event {
+                        PollEvent::NewReceivedOffer(mut offer) => {
+                            if offer.trade_offer_state == TradeOfferState::Active {
+                                accept_free_items(&manager, &mut offer).await;
+                            }
+                        },
+                        PollEvent::AutoCancelled { offer, .. } => {
+                            println!("{} Cancelled by the manager", offer.bright_magenta().bold());
+                        },
+                        event => if let Some(state) = event.previous_state() {
+                            let offer = event.offer();
                     
-                    if offer.trade_offer_state == TradeOfferState::Active {
-                        accept_free_items(&manager, &mut offer).await;
+                            println!(
+                                "{} Offer changed state: {state} -> {}",
+                                offer.bright_magenta().bold(),
+                                offer.trade_offer_state,
+                            );
+                        },
                     }
                 }
             },
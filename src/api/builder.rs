@@ -1,7 +1,8 @@
 use super::{SteamTradeOfferAPI, DEFAULT_GET_INVENTORY_PAGE_SIZE};
 use crate::ClassInfoCache;
 use crate::enums::Language;
-use crate::helpers::{default_data_directory, USER_AGENT_STRING};
+use crate::helpers::{default_data_directory, parse_base_url, USER_AGENT_STRING};
+use crate::helpers::{DEFAULT_COMMUNITY_URL, DEFAULT_WEB_API_URL};
 use crate::session::Session;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -37,6 +38,10 @@ pub struct SteamTradeOfferAPIBuilder {
     pub(crate) user_agent: &'static str,
     /// The session.
     pub(crate) session: Option<Arc<RwLock<Option<Session>>>>,
+    /// The base URL for Steam community requests.
+    pub(crate) community_url: String,
+    /// The base URL for Steam Web API requests.
+    pub(crate) api_url: String,
 }
 
 impl Default for SteamTradeOfferAPIBuilder {
@@ -52,6 +57,8 @@ impl Default for SteamTradeOfferAPIBuilder {
             client: None,
             user_agent: USER_AGENT_STRING,
             session: None,
+            community_url: DEFAULT_COMMUNITY_URL.into(),
+            api_url: DEFAULT_WEB_API_URL.into(),
         }
     }
 }
@@ -114,6 +121,28 @@ impl SteamTradeOfferAPIBuilder {
         self
     }
     
+    /// The base URL for Steam community requests, including the scheme and optionally a port e.g.
+    /// `http://127.0.0.1:8080`. Defaults to `https://steamcommunity.com`.
+    /// Panics if the URL is not valid.
+    pub fn community_url<T>(mut self, community_url: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.community_url = parse_base_url(community_url.into());
+        self
+    }
+    
+    /// The base URL for Steam Web API requests, including the scheme and optionally a port e.g.
+    /// `http://127.0.0.1:8080`. Defaults to `https://api.steampowered.com`.
+    /// Panics if the URL is not valid.
+    pub fn api_url<T>(mut self, api_url: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.api_url = parse_base_url(api_url.into());
+        self
+    }
+    
     /// Sets the session.
     pub(crate) fn session(mut self, session: Arc<RwLock<Option<Session>>>) -> Self {
         self.session = Some(session);
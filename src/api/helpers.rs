@@ -1,5 +1,4 @@
 use super::response as api_response;
-use super::SteamTradeOfferAPI;
 use crate::response::{self, User, UserDetails};
 use crate::types::ClassInfoMap;
 use crate::SteamID;
@@ -9,6 +8,7 @@ use lazy_regex::regex_captures;
 use lazy_regex::Regex;
 
 pub fn offer_referer_url(
+    community_url: &str,
     pathname: &str,
     partner: SteamID,
     token: &Option<&str>,
@@ -21,7 +21,7 @@ pub fn offer_referer_url(
         params.push(("token", token.to_string()));
     }
     
-    let url = SteamTradeOfferAPI::get_url(&format!("/tradeoffer/{pathname}"));
+    let url = format!("{community_url}/tradeoffer/{pathname}");
     let url = reqwest::Url::parse_with_params(&url, &params)
         .map_err(ParameterError::UrlParse)?;
     
@@ -119,6 +119,7 @@ pub fn parse_receipt_script(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::helpers::DEFAULT_COMMUNITY_URL;
     
     #[test]
     fn parses_receipt_script_correctly() {
@@ -152,6 +153,7 @@ mod tests {
     #[test]
     fn gets_offer_referer_url() {
         let url = offer_referer_url(
+            DEFAULT_COMMUNITY_URL,
             "new",
             SteamID::try_from(76561198000000000).unwrap(), 
             &Some("token"),
@@ -160,6 +162,7 @@ mod tests {
         assert_eq!(url, "https://steamcommunity.com/tradeoffer/new?partner=39734272&token=token");
         
         let url = offer_referer_url(
+            DEFAULT_COMMUNITY_URL,
             "new",
             SteamID::try_from(76561198000000000).unwrap(), 
             &None,
@@ -2,11 +2,13 @@
 //! you want more direct control over the requests, you can use this API directly.
 
 pub use builder::SteamTradeOfferAPIBuilder;
+pub use trade_offer_api::TradeOfferApi;
 
 pub mod response;
 pub mod request;
 
 mod builder;
+mod trade_offer_api;
 mod response_wrappers;
 mod helpers;
 
@@ -18,7 +20,6 @@ use response_wrappers::*;
 use crate::classinfo_cache::{ClassInfoCache, helpers as classinfo_cache_helpers};
 use crate::enums::{Language, GetUserDetailsMethod};
 use crate::error::{Result, Error, ParameterError, MissingClassInfoError, SetCookiesError};
-use crate::helpers::{COMMUNITY_HOSTNAME, WEB_API_HOSTNAME};
 use crate::helpers::{get_default_client, parses_response, get_session_from_cookies};
 use crate::request::{GetInventoryOptions, NewTradeOffer, NewTradeOfferItem, GetTradeHistoryOptions};
 use crate::response::*;
@@ -58,31 +59,32 @@ pub struct SteamTradeOfferAPI {
     classinfo_cache: ClassInfoCache,
     /// The directory to store [`ClassInfo`] data.
     pub(crate) data_directory: PathBuf,
+    /// The base URL for Steam community requests.
+    community_url: String,
+    /// The base URL for Steam Web API requests.
+    api_url: String,
 }
 
 impl SteamTradeOfferAPI {
-    /// Hostname for requests.
-    const HOSTNAME: &'static str = COMMUNITY_HOSTNAME;
-    /// Hostname for API requests.
-    const API_HOSTNAME: &'static str = WEB_API_HOSTNAME;
-    
     /// Builder for constructing a [`SteamTradeOfferAPI`].
     pub fn builder() -> SteamTradeOfferAPIBuilder {
         SteamTradeOfferAPIBuilder::new()
     }
     
     fn get_url(
+        &self,
         pathname: &str,
     ) -> String {
-        format!("https://{}{pathname}", Self::HOSTNAME)
+        format!("{}{pathname}", self.community_url)
     }
     
     fn get_api_url(
+        &self,
         interface: &str,
         method: &str,
         version: usize,
     ) -> String {
-        format!("https://{}/{interface}/{method}/v{version}", Self::API_HOSTNAME)
+        format!("{}/{interface}/{method}/v{version}", self.api_url)
     }
     
     /// Gets an owned session ID from the session.
@@ -135,10 +137,10 @@ impl SteamTradeOfferAPI {
         mut cookies: Vec<String>,
     ) -> std::result::Result<(), SetCookiesError> {
         let session = get_session_from_cookies(&mut cookies)?;
-        // Should not panic since the URL is hardcoded.
-        let url = format!("https://{}", Self::HOSTNAME).parse::<Url>()
+        // Should not panic since the URL is checked when building.
+        let url = self.community_url.parse::<Url>()
             .unwrap_or_else(|error| {
-                panic!("URL could not be parsed from {}: {}", Self::HOSTNAME, error)
+                panic!("URL could not be parsed from {}: {}", self.community_url, error)
             });
         
         *self.session.write().unwrap() = Some(session);
@@ -203,7 +205,12 @@ impl SteamTradeOfferAPI {
                 None => String::from("new"),
             };
             
-            helpers::offer_referer_url(&pathname, offer.partner, &offer.token.as_deref())?
+            helpers::offer_referer_url(
+                &self.community_url,
+                &pathname,
+                offer.partner,
+                &offer.token.as_deref(),
+            )?
         };
         let params: SendOfferParams<'_> = {
             let json_tradeoffer = serde_json::to_string(&OfferForm {
@@ -236,7 +243,7 @@ impl SteamTradeOfferAPI {
                 tradeofferid_countered: &counter_tradeofferid,
             }
         };
-        let uri = Self::get_url("/tradeoffer/new/send");
+        let uri = self.get_url("/tradeoffer/new/send");
         let response = self.client.post(&uri)
             .header(REFERER, referer)
             .form(&params)
@@ -252,7 +259,7 @@ impl SteamTradeOfferAPI {
         &self,
         trade_id: &TradeId,
     ) -> Result<Vec<Asset>> {
-        let uri = Self::get_url(&format!("/trade/{trade_id}/receipt"));
+        let uri = self.get_url(&format!("/trade/{trade_id}/receipt"));
         let response = self.client.get(&uri)
             .send()
             .await?;
@@ -327,7 +334,7 @@ impl SteamTradeOfferAPI {
             
             query
         };
-        let uri = Self::get_api_url("ISteamEconomy", "GetAssetClassInfo", 1);
+        let uri = self.get_api_url("ISteamEconomy", "GetAssetClassInfo", 1);
         let response = self.client.get(&uri)
             .query(&query)
             .send()
@@ -434,7 +441,7 @@ impl SteamTradeOfferAPI {
         
         for (appid, classid, instanceid) in needed {
             apps.entry(*appid)
-                .or_insert_with(Vec::new)
+                .or_default()
                 .push((*classid, *instanceid));
         }
         
@@ -482,7 +489,7 @@ impl SteamTradeOfferAPI {
             get_descriptions,
             historical_cutoff,
         } = options;
-        let uri = Self::get_api_url("IEconService", "GetTradeOffers", 1);
+        let uri = self.get_api_url("IEconService", "GetTradeOffers", 1);
         let (key, access_token) = self.get_api_key_and_access_token()?;
         let mut cursor = None;
         let time_historical_cutoff = historical_cutoff
@@ -559,22 +566,7 @@ impl SteamTradeOfferAPI {
         &self,
         offers: Vec<response::RawTradeOffer>,
     ) -> Result<Vec<TradeOffer>> {
-        let classes = offers
-            .iter()
-            .flat_map(|offer| {
-                offer.items_to_give
-                    .iter()
-                    .chain(offer.items_to_receive.iter())
-                    .map(|item| (item.appid, item.classid, item.instanceid))
-            })
-            // make unique
-            .collect::<HashSet<_>>()
-            .into_iter()
-            .collect::<Vec<_>>();
-        let map = self.get_asset_classinfos(&classes).await?;
-        let offers = self.map_raw_trade_offers_with_descriptions(offers, map);
-        
-        Ok(offers)
+        TradeOfferApi::map_raw_trade_offers(self, offers).await
     }
     
     /// Maps trade offer data with given descriptions. Ignores offers with missing descriptions.
@@ -583,13 +575,7 @@ impl SteamTradeOfferAPI {
         offers: Vec<RawTradeOffer>,
         map: ClassInfoMap,
     ) -> Vec<TradeOffer> {
-        offers
-            .into_iter()
-            // ignore offers where the classinfo cannot be obtained
-            // attempts to load the missing classinfos will continue
-            // but it will not cause the whole poll to fail
-            .filter_map(|offer| offer.try_combine_classinfos(&map).ok())
-            .collect()
+        TradeOfferApi::map_raw_trade_offers_with_descriptions(self, offers, map)
     }
     
     /// Gets trade offers.
@@ -597,10 +583,7 @@ impl SteamTradeOfferAPI {
         &self,
         options: &request::GetTradeOffersOptions,
     ) -> Result<Vec<TradeOffer>> {
-        let (raw_offers, _descriptions) = self.get_raw_trade_offers(options).await?;
-        let offers = self.map_raw_trade_offers(raw_offers).await?;
-        
-        Ok(offers)
+        TradeOfferApi::get_trade_offers(self, options).await
     }
     
     /// Gets a trade offer.
@@ -625,7 +608,7 @@ impl SteamTradeOfferAPI {
             response: Body,
         }
         
-        let uri = Self::get_api_url("IEconService", "GetTradeOffer", 1);
+        let uri = self.get_api_url("IEconService", "GetTradeOffer", 1);
         let (key, access_token) = self.get_api_key_and_access_token()?;
         let response = self.client.get(&uri)
             .query(&Form {
@@ -735,7 +718,7 @@ impl SteamTradeOfferAPI {
         let start_after_time = start_after_time
             .map(|time| time.timestamp() as u32);
         let (key, access_token) = self.get_api_key_and_access_token()?;
-        let uri = Self::get_api_url("IEconService", "GetTradeHistory", 1);
+        let uri = self.get_api_url("IEconService", "GetTradeHistory", 1);
         let response = self.client.get(&uri)
             .query(&Form {
                 key,
@@ -755,6 +738,28 @@ impl SteamTradeOfferAPI {
         Ok(body.response)
     }
     
+    /// Checks whether the session is still valid using a cheap authenticated request. Returns
+    /// `false` if Steam does not consider us logged in.
+    pub async fn check_session(
+        &self,
+    ) -> Result<bool> {
+        #[derive(Deserialize)]
+        struct Body {
+            logged_in: bool,
+        }
+        
+        let uri = self.get_url("/chat/clientjstoken");
+        let response = self.client.get(&uri)
+            .send()
+            .await?;
+        
+        match parses_response::<Body>(response).await {
+            Ok(body) => Ok(body.logged_in),
+            Err(Error::NotLoggedIn) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+    
     /// Gets escrow details for a user. The `method` for obtaining details can be a `tradeofferid`
     /// or `access_token` or neither.
     pub async fn get_user_details<T>(
@@ -769,7 +774,7 @@ impl SteamTradeOfferAPI {
             let method = method.into();
             let pathname = method.pathname();
             
-            helpers::offer_referer_url(&pathname, partner, &method.token())?
+            helpers::offer_referer_url(&self.community_url, &pathname, partner, &method.token())?
         };
         let response = self.client.get(&uri)
             .send()
@@ -800,7 +805,7 @@ impl SteamTradeOfferAPI {
         }
         
         let sessionid = self.get_sessionid()?;
-        let referer = Self::get_url(&format!("/tradeoffer/{tradeofferid}"));
+        let referer = self.get_url(&format!("/tradeoffer/{tradeofferid}"));
         let params = AcceptOfferParams {
             sessionid,
             tradeofferid,
@@ -808,7 +813,7 @@ impl SteamTradeOfferAPI {
             serverid: 1,
             captcha: "",
         };
-        let uri = Self::get_url(&format!("/tradeoffer/{tradeofferid}/accept"));
+        let uri = self.get_url(&format!("/tradeoffer/{tradeofferid}/accept"));
         let response = self.client.post(&uri)
             .header(REFERER, referer)
             .form(&params)
@@ -836,8 +841,8 @@ impl SteamTradeOfferAPI {
         }
         
         let sessionid = self.get_sessionid()?;
-        let referer = Self::get_url(&format!("/tradeoffer/{tradeofferid}"));
-        let uri = Self::get_url(&format!("/tradeoffer/{tradeofferid}/decline"));
+        let referer = self.get_url(&format!("/tradeoffer/{tradeofferid}"));
+        let uri = self.get_url(&format!("/tradeoffer/{tradeofferid}/decline"));
         let response = self.client.post(&uri)
             .header(REFERER, referer)
             .form(&DeclineOfferParams {
@@ -867,8 +872,8 @@ impl SteamTradeOfferAPI {
         }
         
         let sessionid = self.get_sessionid()?;
-        let referer = Self::get_url(&format!("/tradeoffer/{tradeofferid}"));
-        let uri = Self::get_url(&format!("/tradeoffer/{tradeofferid}/cancel"));
+        let referer = self.get_url(&format!("/tradeoffer/{tradeofferid}"));
+        let uri = self.get_url(&format!("/tradeoffer/{tradeofferid}/cancel"));
         let response = self.client.post(&uri)
             .header(REFERER, referer)
             .form(&CancelOfferParams {
@@ -899,10 +904,10 @@ impl SteamTradeOfferAPI {
         let mut responses: Vec<GetInventoryOldResponse> = Vec::new();
         let mut start: Option<u64> = None;
         let steamid_64 = u64::from(steamid);
-        let uri = Self::get_url(
+        let uri = self.get_url(
             &format!("/profiles/{steamid_64}/inventory/json/{appid}/{contextid}")
         );
-        let referer = Self::get_url(
+        let referer = self.get_url(
             &format!("/profiles/{steamid_64}/inventory")
         );
         
@@ -985,6 +990,7 @@ impl SteamTradeOfferAPI {
             tradable_only,
             language: self.language,
             page_size: self.get_inventory_page_size,
+            community_url: Some(&self.community_url),
         }).await
     }
     
@@ -1014,10 +1020,10 @@ impl SteamTradeOfferAPI {
         // Not required, but included when available.
         let access_token = self.get_access_token().ok();
         let steamid_64 = u64::from(steamid);
-        let uri = Self::get_url(
+        let uri = self.get_url(
             &format!("/inventory/{steamid_64}/{appid}/{contextid}")
         );
-        let referer = Self::get_url(
+        let referer = self.get_url(
             &format!("/profiles/{steamid_64}/inventory")
         );
         let mut asset_properties = HashMap::new();
@@ -1123,6 +1129,8 @@ impl From<SteamTradeOfferAPIBuilder> for SteamTradeOfferAPI {
             get_inventory_page_size: builder.get_inventory_page_size,
             classinfo_cache: builder.classinfo_cache.unwrap_or_default(),
             data_directory: builder.data_directory,
+            community_url: builder.community_url,
+            api_url: builder.api_url,
         }
     }
 }
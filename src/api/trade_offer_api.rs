@@ -0,0 +1,409 @@
+use super::SteamTradeOfferAPI;
+use super::request::GetTradeOffersOptions;
+use super::response::RawTradeOffer;
+use crate::enums::GetUserDetailsMethod;
+use crate::error::Result;
+use crate::request::{GetTradeHistoryOptions, NewTradeOffer};
+use crate::response::{AcceptedOffer, Asset, SentOffer, TradeOffer, Trades, UserDetails};
+use crate::types::{AppId, ClassInfoClass, ClassInfoMap, ContextId, TradeId, TradeOfferId};
+use crate::SteamID;
+use std::collections::HashSet;
+use std::fmt::Debug;
+use async_trait::async_trait;
+
+/// The requests the [`TradeOfferManager`](crate::TradeOfferManager) makes for trade offers,
+/// inventories and trade history. [`SteamTradeOfferAPI`] is the default implementation.
+///
+/// Implement this trait to replace the requests with fakes in tests, or to wrap the default API
+/// with decorators such as logging or rate limiting. Set it using
+/// [`TradeOfferManagerBuilder::trade_offer_api`](crate::TradeOfferManagerBuilder::trade_offer_api).
+///
+/// # Examples
+/// ```
+/// use steam_tradeoffer_manager::SteamID;
+/// use steam_tradeoffer_manager::api::{SteamTradeOfferAPI, TradeOfferApi};
+/// use steam_tradeoffer_manager::api::request::GetTradeOffersOptions;
+/// use steam_tradeoffer_manager::api::response::RawTradeOffer;
+/// use steam_tradeoffer_manager::enums::GetUserDetailsMethod;
+/// use steam_tradeoffer_manager::error::Error;
+/// use steam_tradeoffer_manager::request::{GetTradeHistoryOptions, NewTradeOffer};
+/// use steam_tradeoffer_manager::response::*;
+/// use steam_tradeoffer_manager::types::*;
+///
+/// // Logs each offer that is sent.
+/// #[derive(Debug)]
+/// struct LoggingApi(SteamTradeOfferAPI);
+///
+/// #[steam_tradeoffer_manager::async_trait]
+/// impl TradeOfferApi for LoggingApi {
+///     async fn send_offer(
+///         &self,
+///         offer: &NewTradeOffer,
+///         counter_tradeofferid: Option<TradeOfferId>,
+///     ) -> Result<SentOffer, Error> {
+///         let sent_offer = self.0.send_offer(offer, counter_tradeofferid).await?;
+///
+///         println!("Sent offer {}", sent_offer.tradeofferid);
+///         Ok(sent_offer)
+///     }
+///
+///     async fn get_raw_trade_offers(
+///         &self,
+///         options: &GetTradeOffersOptions,
+///     ) -> Result<(Vec<RawTradeOffer>, Option<ClassInfoMap>), Error> {
+///         self.0.get_raw_trade_offers(options).await
+///     }
+///
+///     async fn get_asset_classinfos(
+///         &self,
+///         classes: &[ClassInfoClass],
+///     ) -> Result<ClassInfoMap, Error> {
+///         self.0.get_asset_classinfos(classes).await
+///     }
+///
+///     async fn get_trade_offer(
+///         &self,
+///         tradeofferid: TradeOfferId,
+///     ) -> Result<RawTradeOffer, Error> {
+///         self.0.get_trade_offer(tradeofferid).await
+///     }
+///
+///     async fn accept_offer(
+///         &self,
+///         tradeofferid: TradeOfferId,
+///         partner: SteamID,
+///     ) -> Result<AcceptedOffer, Error> {
+///         self.0.accept_offer(tradeofferid, partner).await
+///     }
+///
+///     async fn decline_offer(
+///         &self,
+///         tradeofferid: TradeOfferId,
+///     ) -> Result<TradeOfferId, Error> {
+///         self.0.decline_offer(tradeofferid).await
+///     }
+///
+///     async fn cancel_offer(
+///         &self,
+///         tradeofferid: TradeOfferId,
+///     ) -> Result<TradeOfferId, Error> {
+///         self.0.cancel_offer(tradeofferid).await
+///     }
+///
+///     async fn get_trade_history(
+///         &self,
+///         options: &GetTradeHistoryOptions,
+///     ) -> Result<Trades, Error> {
+///         self.0.get_trade_history(options).await
+///     }
+///
+///     async fn get_receipt(
+///         &self,
+///         tradeid: &TradeId,
+///     ) -> Result<Vec<Asset>, Error> {
+///         self.0.get_receipt(tradeid).await
+///     }
+///
+///     async fn get_inventory(
+///         &self,
+///         steamid: SteamID,
+///         appid: AppId,
+///         contextid: ContextId,
+///         tradable_only: bool,
+///     ) -> Result<Vec<Asset>, Error> {
+///         self.0.get_inventory(steamid, appid, contextid, tradable_only).await
+///     }
+///
+///     async fn get_user_details(
+///         &self,
+///         partner: SteamID,
+///         method: GetUserDetailsMethod,
+///     ) -> Result<UserDetails, Error> {
+///         self.0.get_user_details(partner, method).await
+///     }
+///
+///     async fn check_session(
+///         &self,
+///     ) -> Result<bool, Error> {
+///         self.0.check_session().await
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait TradeOfferApi: Debug + Send + Sync {
+    /// Gets trade offer data before any descriptions are added. The 2nd part of the tuple are the
+    /// descriptions from the response if `get_descriptions` was set.
+    async fn get_raw_trade_offers(
+        &self,
+        options: &GetTradeOffersOptions,
+    ) -> Result<(Vec<RawTradeOffer>, Option<ClassInfoMap>)>;
+    
+    /// Gets [`ClassInfo`](crate::response::ClassInfo) data for the given classes.
+    async fn get_asset_classinfos(
+        &self,
+        classes: &[ClassInfoClass],
+    ) -> Result<ClassInfoMap>;
+    
+    /// Gets a trade offer.
+    async fn get_trade_offer(
+        &self,
+        tradeofferid: TradeOfferId,
+    ) -> Result<RawTradeOffer>;
+    
+    /// Sends an offer. Counters the offer `counter_tradeofferid` if set.
+    async fn send_offer(
+        &self,
+        offer: &NewTradeOffer,
+        counter_tradeofferid: Option<TradeOfferId>,
+    ) -> Result<SentOffer>;
+    
+    /// Accepts an offer.
+    async fn accept_offer(
+        &self,
+        tradeofferid: TradeOfferId,
+        partner: SteamID,
+    ) -> Result<AcceptedOffer>;
+    
+    /// Declines an offer.
+    async fn decline_offer(
+        &self,
+        tradeofferid: TradeOfferId,
+    ) -> Result<TradeOfferId>;
+    
+    /// Cancels an offer.
+    async fn cancel_offer(
+        &self,
+        tradeofferid: TradeOfferId,
+    ) -> Result<TradeOfferId>;
+    
+    /// Gets trade history.
+    async fn get_trade_history(
+        &self,
+        options: &GetTradeHistoryOptions,
+    ) -> Result<Trades>;
+    
+    /// Gets the trade receipt (new items) upon completion of a trade.
+    async fn get_receipt(
+        &self,
+        tradeid: &TradeId,
+    ) -> Result<Vec<Asset>>;
+    
+    /// Gets a user's inventory.
+    async fn get_inventory(
+        &self,
+        steamid: SteamID,
+        appid: AppId,
+        contextid: ContextId,
+        tradable_only: bool,
+    ) -> Result<Vec<Asset>>;
+    
+    /// Gets escrow details for a user.
+    async fn get_user_details(
+        &self,
+        partner: SteamID,
+        method: GetUserDetailsMethod,
+    ) -> Result<UserDetails>;
+    
+    /// Checks whether the session is still valid. Returns `false` if Steam does not consider us
+    /// logged in.
+    async fn check_session(
+        &self,
+    ) -> Result<bool>;
+    
+    /// Checks that the credentials needed for requests are set. Called before polling starts.
+    /// By default, no credentials are required. Decorators should delegate this to the API they
+    /// wrap.
+    fn check_credentials(
+        &self,
+    ) -> Result<()> {
+        Ok(())
+    }
+    
+    /// Combines trade offers with their descriptions using
+    /// [`TradeOfferApi::get_asset_classinfos`]. Ignores offers with missing descriptions.
+    async fn map_raw_trade_offers(
+        &self,
+        offers: Vec<RawTradeOffer>,
+    ) -> Result<Vec<TradeOffer>> {
+        let classes = offers
+            .iter()
+            .flat_map(|offer| {
+                offer.items_to_give
+                    .iter()
+                    .chain(offer.items_to_receive.iter())
+                    .map(|item| (item.appid, item.classid, item.instanceid))
+            })
+            // make unique
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let map = self.get_asset_classinfos(&classes).await?;
+        let offers = self.map_raw_trade_offers_with_descriptions(offers, map);
+        
+        Ok(offers)
+    }
+    
+    /// Maps trade offer data with given descriptions. Ignores offers with missing descriptions.
+    fn map_raw_trade_offers_with_descriptions(
+        &self,
+        offers: Vec<RawTradeOffer>,
+        map: ClassInfoMap,
+    ) -> Vec<TradeOffer> {
+        offers
+            .into_iter()
+            // ignore offers where the classinfo cannot be obtained
+            // attempts to load the missing classinfos will continue
+            // but it will not cause the whole poll to fail
+            .filter_map(|offer| offer.try_combine_classinfos(&map).ok())
+            .collect()
+    }
+    
+    /// Gets trade offers.
+    async fn get_trade_offers(
+        &self,
+        options: &GetTradeOffersOptions,
+    ) -> Result<Vec<TradeOffer>> {
+        let (raw_offers, _descriptions) = self.get_raw_trade_offers(options).await?;
+        
+        self.map_raw_trade_offers(raw_offers).await
+    }
+}
+
+#[async_trait]
+impl TradeOfferApi for SteamTradeOfferAPI {
+    async fn get_raw_trade_offers(
+        &self,
+        options: &GetTradeOffersOptions,
+    ) -> Result<(Vec<RawTradeOffer>, Option<ClassInfoMap>)> {
+        SteamTradeOfferAPI::get_raw_trade_offers(self, options).await
+    }
+    
+    async fn get_asset_classinfos(
+        &self,
+        classes: &[ClassInfoClass],
+    ) -> Result<ClassInfoMap> {
+        SteamTradeOfferAPI::get_asset_classinfos(self, classes).await
+    }
+    
+    async fn get_trade_offer(
+        &self,
+        tradeofferid: TradeOfferId,
+    ) -> Result<RawTradeOffer> {
+        SteamTradeOfferAPI::get_trade_offer(self, tradeofferid).await
+    }
+    
+    async fn send_offer(
+        &self,
+        offer: &NewTradeOffer,
+        counter_tradeofferid: Option<TradeOfferId>,
+    ) -> Result<SentOffer> {
+        SteamTradeOfferAPI::send_offer(self, offer, counter_tradeofferid).await
+    }
+    
+    async fn accept_offer(
+        &self,
+        tradeofferid: TradeOfferId,
+        partner: SteamID,
+    ) -> Result<AcceptedOffer> {
+        SteamTradeOfferAPI::accept_offer(self, tradeofferid, partner).await
+    }
+    
+    async fn decline_offer(
+        &self,
+        tradeofferid: TradeOfferId,
+    ) -> Result<TradeOfferId> {
+        SteamTradeOfferAPI::decline_offer(self, tradeofferid).await
+    }
+    
+    async fn cancel_offer(
+        &self,
+        tradeofferid: TradeOfferId,
+    ) -> Result<TradeOfferId> {
+        SteamTradeOfferAPI::cancel_offer(self, tradeofferid).await
+    }
+    
+    async fn get_trade_history(
+        &self,
+        options: &GetTradeHistoryOptions,
+    ) -> Result<Trades> {
+        SteamTradeOfferAPI::get_trade_history(self, options).await
+    }
+    
+    async fn get_receipt(
+        &self,
+        tradeid: &TradeId,
+    ) -> Result<Vec<Asset>> {
+        SteamTradeOfferAPI::get_receipt(self, tradeid).await
+    }
+    
+    async fn get_inventory(
+        &self,
+        steamid: SteamID,
+        appid: AppId,
+        contextid: ContextId,
+        tradable_only: bool,
+    ) -> Result<Vec<Asset>> {
+        SteamTradeOfferAPI::get_inventory(self, steamid, appid, contextid, tradable_only).await
+    }
+    
+    async fn get_user_details(
+        &self,
+        partner: SteamID,
+        method: GetUserDetailsMethod,
+    ) -> Result<UserDetails> {
+        SteamTradeOfferAPI::get_user_details(self, partner, method).await
+    }
+    
+    async fn check_session(
+        &self,
+    ) -> Result<bool> {
+        SteamTradeOfferAPI::check_session(self).await
+    }
+    
+    fn check_credentials(
+        &self,
+    ) -> Result<()> {
+        self.get_api_key_and_access_token().map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TradeOfferManager;
+    use crate::enums::TradeOfferState;
+    use crate::fixtures::{raw_asset, raw_offer, FakeApi};
+    use std::sync::{Arc, Mutex};
+    
+    fn received_offer(tradeofferid: TradeOfferId, classid: u64) -> RawTradeOffer {
+        RawTradeOffer {
+            items_to_receive: vec![raw_asset(classid)],
+            is_our_offer: false,
+            ..raw_offer(tradeofferid, TradeOfferState::Active)
+        }
+    }
+    
+    #[tokio::test]
+    async fn manager_uses_injected_api() {
+        let declined = Arc::new(Mutex::new(Vec::new()));
+        let fake_declined = Arc::clone(&declined);
+        let manager = TradeOfferManager::builder()
+            .trade_offer_api(move |_api| FakeApi {
+                // The offer with class 2 is missing its classinfo and is excluded.
+                offers: Mutex::new(vec![received_offer(1, 1), received_offer(2, 2)]),
+                missing_classids: vec![2],
+                declined: Arc::clone(&fake_declined),
+                ..Default::default()
+            })
+            .build();
+        let mut offers = manager.get_active_trade_offers().await.unwrap();
+        
+        assert_eq!(offers.len(), 1);
+        assert_eq!(offers[0].tradeofferid, 1);
+        
+        manager.decline_offer(&mut offers[0]).await.unwrap();
+        
+        assert_eq!(offers[0].trade_offer_state, TradeOfferState::Declined);
+        assert_eq!(*declined.lock().unwrap(), vec![1]);
+    }
+}
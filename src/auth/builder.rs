@@ -0,0 +1,114 @@
+use super::AuthClient;
+use crate::helpers::{
+    parse_base_url,
+    DEFAULT_COMMUNITY_URL,
+    DEFAULT_LOGIN_URL,
+    DEFAULT_WEB_API_URL,
+    USER_AGENT_STRING,
+};
+use reqwest_middleware::ClientWithMiddleware;
+use chrono::Duration;
+
+const DEFAULT_POLL_TIMEOUT_SECONDS: i64 = 30;
+
+/// Builder for constructing an [`AuthClient`].
+#[derive(Debug, Clone)]
+pub struct AuthClientBuilder {
+    /// Client to use for requests.
+    pub(crate) client: Option<ClientWithMiddleware>,
+    /// User agent for requests.
+    pub(crate) user_agent: &'static str,
+    /// The base URL for Steam Web API requests.
+    pub(crate) api_url: String,
+    /// The base URL for finalizing logins.
+    pub(crate) login_url: String,
+    /// The base URL for the Steam community.
+    pub(crate) community_url: String,
+    /// How many seconds your computer is behind Steam's servers. Used in Steam Guard codes.
+    pub(crate) time_offset: i64,
+    /// How long to wait for the auth session to be approved.
+    pub(crate) poll_timeout: Duration,
+}
+
+impl Default for AuthClientBuilder {
+    fn default() -> Self {
+        Self {
+            client: None,
+            user_agent: USER_AGENT_STRING,
+            api_url: DEFAULT_WEB_API_URL.into(),
+            login_url: DEFAULT_LOGIN_URL.into(),
+            community_url: DEFAULT_COMMUNITY_URL.into(),
+            time_offset: 0,
+            // unwrap is safe because the value is in range
+            poll_timeout: Duration::try_seconds(DEFAULT_POLL_TIMEOUT_SECONDS).unwrap(),
+        }
+    }
+}
+
+impl AuthClientBuilder {
+    /// Creates a new [`AuthClientBuilder`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Client to use for requests.
+    pub fn client(mut self, client: ClientWithMiddleware) -> Self {
+        self.client = Some(client);
+        self
+    }
+    
+    /// User agent for requests.
+    pub(crate) fn user_agent(mut self, user_agent: &'static str) -> Self {
+        self.user_agent = user_agent;
+        self
+    }
+    
+    /// The base URL for Steam Web API requests, including the scheme and optionally a port e.g.
+    /// `http://127.0.0.1:8080`. Defaults to `https://api.steampowered.com`.
+    /// Panics if the URL is not valid.
+    pub fn api_url<T>(mut self, api_url: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.api_url = parse_base_url(api_url.into());
+        self
+    }
+    
+    /// The base URL for finalizing logins. Defaults to `https://login.steampowered.com`.
+    /// Panics if the URL is not valid.
+    pub fn login_url<T>(mut self, login_url: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.login_url = parse_base_url(login_url.into());
+        self
+    }
+    
+    /// The base URL for the Steam community. Cookies are taken from the login transfer to this
+    /// URL. Defaults to `https://steamcommunity.com`. Panics if the URL is not valid.
+    pub fn community_url<T>(mut self, community_url: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.community_url = parse_base_url(community_url.into());
+        self
+    }
+    
+    /// How many seconds your computer is behind Steam's servers. Used in Steam Guard codes.
+    pub fn time_offset(mut self, time_offset: i64) -> Self {
+        self.time_offset = time_offset;
+        self
+    }
+    
+    /// How long to wait for the auth session to be approved after submitting credentials.
+    /// Defaults to 30 seconds.
+    pub fn poll_timeout(mut self, poll_timeout: Duration) -> Self {
+        self.poll_timeout = poll_timeout;
+        self
+    }
+    
+    /// Builds the [`AuthClient`].
+    pub fn build(self) -> AuthClient {
+        self.into()
+    }
+}
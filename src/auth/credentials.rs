@@ -0,0 +1,47 @@
+use std::fmt;
+
+/// The credentials for logging in to an account.
+#[derive(Clone)]
+pub struct Credentials {
+    /// The account name.
+    pub account_name: String,
+    /// The password.
+    pub password: String,
+    /// The shared secret for generating Steam Guard codes. Required if the account has the
+    /// Steam Guard mobile authenticator enabled.
+    pub shared_secret: Option<String>,
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("account_name", &self.account_name)
+            .field("password", &"<redacted>")
+            .field("shared_secret", &self.shared_secret.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+impl Credentials {
+    /// Creates new [`Credentials`].
+    pub fn new<T, U>(account_name: T, password: U) -> Self
+    where
+        T: Into<String>,
+        U: Into<String>,
+    {
+        Self {
+            account_name: account_name.into(),
+            password: password.into(),
+            shared_secret: None,
+        }
+    }
+    
+    /// Sets the shared secret for generating Steam Guard codes.
+    pub fn shared_secret<T>(mut self, shared_secret: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.shared_secret = Some(shared_secret.into());
+        self
+    }
+}
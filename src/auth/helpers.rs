@@ -0,0 +1,114 @@
+use crate::error::AuthError;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use reqwest::header::{HeaderMap, SET_COOKIE};
+use rsa::{BigUint, Pkcs1v15Encrypt, RsaPublicKey};
+use rsa::rand_core::OsRng;
+
+/// No Steam Guard confirmation is required.
+const CONFIRMATION_TYPE_NONE: u32 = 1;
+/// A Steam Guard code from the mobile authenticator.
+const CONFIRMATION_TYPE_DEVICE_CODE: u32 = 3;
+
+/// How the login should be confirmed.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SteamGuard {
+    /// No confirmation is needed.
+    None,
+    /// A Steam Guard code from the mobile authenticator is needed.
+    DeviceCode,
+}
+
+/// Encrypts the password using the RSA key for the account. Returns the base64-encoded result.
+pub fn encrypt_password(
+    password: &str,
+    publickey_mod: &str,
+    publickey_exp: &str,
+) -> Result<String, AuthError> {
+    let modulus = BigUint::parse_bytes(publickey_mod.as_bytes(), 16)
+        .ok_or(AuthError::EncryptPassword("Invalid RSA modulus".into()))?;
+    let exponent = BigUint::parse_bytes(publickey_exp.as_bytes(), 16)
+        .ok_or(AuthError::EncryptPassword("Invalid RSA exponent".into()))?;
+    let key = RsaPublicKey::new(modulus, exponent)
+        .map_err(|error| AuthError::EncryptPassword(error.to_string()))?;
+    let encrypted = key.encrypt(&mut OsRng, Pkcs1v15Encrypt, password.as_bytes())
+        .map_err(|error| AuthError::EncryptPassword(error.to_string()))?;
+    
+    Ok(STANDARD.encode(encrypted))
+}
+
+/// Determines how to confirm the login from the confirmation types Steam allows.
+pub fn get_steam_guard(
+    allowed_confirmations: &[u32],
+) -> Result<SteamGuard, AuthError> {
+    if allowed_confirmations.contains(&CONFIRMATION_TYPE_NONE) {
+        return Ok(SteamGuard::None);
+    }
+    
+    if allowed_confirmations.contains(&CONFIRMATION_TYPE_DEVICE_CODE) {
+        return Ok(SteamGuard::DeviceCode);
+    }
+    
+    Err(AuthError::UnsupportedConfirmation(allowed_confirmations.to_vec()))
+}
+
+/// Gets the cookies set in the response headers in `name=value` form.
+pub fn get_set_cookies(
+    headers: &HeaderMap,
+) -> Vec<String> {
+    headers.get_all(SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|value| value.split(';').next())
+        .map(|cookie| cookie.trim().to_string())
+        // Cookies being deleted have no value.
+        .filter(|cookie| cookie.split_once('=').is_some_and(|(_, value)| !value.is_empty()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsa::RsaPrivateKey;
+    use rsa::traits::PublicKeyParts;
+    use reqwest::header::HeaderValue;
+    
+    #[test]
+    fn encrypts_password() {
+        let private_key = RsaPrivateKey::new(&mut OsRng, 512).unwrap();
+        let public_key = private_key.to_public_key();
+        let encrypted = encrypt_password(
+            "hunter2",
+            &public_key.n().to_str_radix(16),
+            &public_key.e().to_str_radix(16),
+        ).unwrap();
+        let decrypted = private_key.decrypt(
+            Pkcs1v15Encrypt,
+            &STANDARD.decode(encrypted).unwrap(),
+        ).unwrap();
+        
+        assert_eq!(decrypted, b"hunter2");
+    }
+    
+    #[test]
+    fn chooses_steam_guard() {
+        assert_eq!(get_steam_guard(&[1]).unwrap(), SteamGuard::None);
+        assert_eq!(get_steam_guard(&[4, 3]).unwrap(), SteamGuard::DeviceCode);
+        assert!(matches!(get_steam_guard(&[2]), Err(AuthError::UnsupportedConfirmation(_))));
+    }
+    
+    #[test]
+    fn gets_set_cookies() {
+        let mut headers = HeaderMap::new();
+        
+        headers.append(
+            SET_COOKIE,
+            HeaderValue::from_static("steamLoginSecure=76561198000000000%7C%7Ctoken; Path=/"),
+        );
+        headers.append(SET_COOKIE, HeaderValue::from_static("steamRefresh_steam=; Max-Age=0"));
+        
+        assert_eq!(get_set_cookies(&headers), vec![
+            "steamLoginSecure=76561198000000000%7C%7Ctoken".to_string(),
+        ]);
+    }
+}
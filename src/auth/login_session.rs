@@ -0,0 +1,30 @@
+use crate::SteamID;
+use std::fmt;
+
+/// The result of a successful login.
+#[derive(Clone)]
+pub struct LoginSession {
+    /// The SteamID of the account.
+    pub steamid: SteamID,
+    /// The account name.
+    pub account_name: String,
+    /// The refresh token. Used to get new access tokens without logging in again.
+    pub refresh_token: String,
+    /// The access token.
+    pub access_token: String,
+    /// The web cookies for the Steam community. Pass these to
+    /// [`TradeOfferManager::set_cookies`](crate::TradeOfferManager::set_cookies).
+    pub cookies: Vec<String>,
+}
+
+impl fmt::Debug for LoginSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoginSession")
+            .field("steamid", &self.steamid)
+            .field("account_name", &self.account_name)
+            .field("refresh_token", &"<redacted>")
+            .field("access_token", &"<redacted>")
+            .field("cookies", &"<redacted>")
+            .finish()
+    }
+}
@@ -0,0 +1,424 @@
+//! Logging in to Steam using account credentials through Steam's `IAuthenticationService`. The
+//! resulting cookies can be passed to
+//! [`TradeOfferManager::set_cookies`](crate::TradeOfferManager::set_cookies).
+//! 
+//! # Examples
+//! ```no_run
+//! use steam_tradeoffer_manager::TradeOfferManager;
+//! use steam_tradeoffer_manager::auth::{AuthClient, Credentials};
+//! 
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     let credentials = Credentials::new("username", "password")
+//!         .shared_secret("secret");
+//!     let session = AuthClient::builder()
+//!         .build()
+//!         .login(&credentials)
+//!         .await?;
+//!     let manager = TradeOfferManager::builder()
+//!         .build();
+//!     
+//!     manager.set_cookies(session.cookies)?;
+//!     Ok(())
+//! }
+//! ```
+
+mod builder;
+mod credentials;
+mod helpers;
+mod login_session;
+mod response;
+
+pub use builder::AuthClientBuilder;
+pub use credentials::Credentials;
+pub use login_session::LoginSession;
+
+use helpers::SteamGuard;
+use response::{
+    ApiResponse,
+    BeginAuthSessionResponse,
+    FinalizeLoginResponse,
+    PollAuthSessionResponse,
+    RsaKeyResponse,
+};
+use crate::SteamID;
+use crate::error::{AuthError, Error, Result};
+use crate::helpers::{generate_sessionid, get_default_client};
+use std::sync::Arc;
+use another_steam_totp::generate_auth_code;
+use chrono::Duration;
+use reqwest::cookie::Jar;
+use reqwest::header::{ORIGIN, REFERER};
+use reqwest::multipart::Form;
+use reqwest_middleware::{ClientWithMiddleware, RequestBuilder};
+use serde::de::DeserializeOwned;
+
+/// The platform type for web browsers. Refresh tokens for this platform are exchanged for cookies
+/// through `/jwt/finalizelogin` rather than `GenerateAccessTokenForApp`.
+const PLATFORM_TYPE_WEB_BROWSER: &str = "2";
+/// Persistent sessions are remembered after the browser is closed.
+const SESSION_PERSISTENCE_PERSISTENT: &str = "1";
+/// The code type for Steam Guard codes from the mobile authenticator.
+const CODE_TYPE_DEVICE_CODE: &str = "3";
+/// The default interval to poll the auth session at in seconds.
+const DEFAULT_POLL_INTERVAL_SECONDS: f64 = 5.0;
+
+/// EResult for a successful request.
+const ERESULT_OK: u32 = 1;
+/// EResult for an invalid account name or password.
+const ERESULT_INVALID_PASSWORD: u32 = 5;
+/// EResult for a request that was already made, e.g. a Steam Guard code already submitted.
+const ERESULT_DUPLICATE_REQUEST: u32 = 29;
+/// EResult for an invalid Steam Guard code.
+const ERESULT_INVALID_LOGIN_AUTH_CODE: u32 = 65;
+/// EResult for too many login attempts.
+const ERESULT_RATE_LIMIT_EXCEEDED: u32 = 84;
+/// EResult for a Steam Guard code that does not match.
+const ERESULT_TWO_FACTOR_CODE_MISMATCH: u32 = 88;
+
+/// Client for logging in to Steam.
+#[derive(Debug, Clone)]
+pub struct AuthClient {
+    /// The client for making requests.
+    client: ClientWithMiddleware,
+    /// The user agent for requests.
+    user_agent: &'static str,
+    /// The base URL for Steam Web API requests.
+    api_url: String,
+    /// The base URL for finalizing logins.
+    login_url: String,
+    /// The base URL for the Steam community.
+    community_url: String,
+    /// How many seconds your computer is behind Steam's servers.
+    time_offset: i64,
+    /// How long to wait for the auth session to be approved.
+    poll_timeout: Duration,
+}
+
+impl AuthClient {
+    /// Builder for constructing an [`AuthClient`].
+    pub fn builder() -> AuthClientBuilder {
+        AuthClientBuilder::new()
+    }
+    
+    /// Logs in using the credentials. If the account has the Steam Guard mobile authenticator
+    /// enabled, a code is generated from [`Credentials::shared_secret`].
+    /// 
+    /// # Errors
+    /// - [`AuthError::InvalidPassword`] if the account name or password is incorrect.
+    /// - [`AuthError::SteamGuardCodeRequired`] if a Steam Guard code is required and no shared
+    ///   secret was provided.
+    /// - [`AuthError::UnsupportedConfirmation`] if the login must be confirmed another way, e.g.
+    ///   by email.
+    /// - [`AuthError::Timeout`] if the auth session was not approved before the poll timeout.
+    /// - Any other error encountered while performing requests.
+    pub async fn login(
+        &self,
+        credentials: &Credentials,
+    ) -> Result<LoginSession> {
+        let rsa_key = self.get_password_rsa_public_key(&credentials.account_name).await?;
+        let encrypted_password = helpers::encrypt_password(
+            &credentials.password,
+            &rsa_key.publickey_mod,
+            &rsa_key.publickey_exp,
+        )?;
+        let auth_session = self.begin_auth_session_via_credentials(
+            &credentials.account_name,
+            &encrypted_password,
+            rsa_key.timestamp,
+        ).await?;
+        let allowed_confirmations = auth_session.allowed_confirmations
+            .iter()
+            .map(|confirmation| confirmation.confirmation_type)
+            .collect::<Vec<_>>();
+        
+        if helpers::get_steam_guard(&allowed_confirmations)? == SteamGuard::DeviceCode {
+            let shared_secret = credentials.shared_secret.as_ref()
+                .ok_or(AuthError::SteamGuardCodeRequired)?;
+            let code = generate_auth_code(shared_secret, Some(self.time_offset))?;
+            
+            self.update_auth_session_with_steam_guard_code(
+                auth_session.client_id,
+                auth_session.steamid,
+                &code,
+            ).await?;
+        }
+        
+        let tokens = self.poll_auth_session_status(&auth_session).await?;
+        let refresh_token = tokens.refresh_token
+            .ok_or(AuthError::MissingToken("refresh_token"))?;
+        let access_token = tokens.access_token
+            .ok_or(AuthError::MissingToken("access_token"))?;
+        let cookies = self.finalize_login(&refresh_token).await?;
+        let steamid = SteamID::try_from(auth_session.steamid)
+            .map_err(|_| Error::MalformedResponse("Invalid SteamID in auth session."))?;
+        
+        Ok(LoginSession {
+            steamid,
+            account_name: tokens.account_name
+                .unwrap_or_else(|| credentials.account_name.clone()),
+            refresh_token,
+            access_token,
+            cookies,
+        })
+    }
+    
+    /// Gets new web cookies using the refresh token from [`AuthClient::login`]. The cookies
+    /// contain a new access token, so this renews a session without logging in again.
+    /// 
+    /// The token is exchanged through `/jwt/finalizelogin`, the same way the Steam website
+    /// renews the web browser sessions [`AuthClient::login`] creates. This is used over
+    /// `GenerateAccessTokenForApp`, which only returns an access token that would still need
+    /// to be turned into cookies, while `/jwt/finalizelogin` sets the cookies on every Steam
+    /// domain in one step.
+    /// 
+    /// # Errors
+    /// - If the refresh token is invalid or expired.
+    /// - Any other error encountered while performing requests.
+    pub async fn get_web_cookies(
+        &self,
+        refresh_token: &str,
+    ) -> Result<Vec<String>> {
+        self.finalize_login(refresh_token).await
+    }
+    
+    async fn get_password_rsa_public_key(
+        &self,
+        account_name: &str,
+    ) -> Result<RsaKeyResponse> {
+        let uri = self.get_api_url("GetPasswordRSAPublicKey");
+        let request = self.client.get(&uri)
+            .query(&[("account_name", account_name)]);
+        
+        self.send_api_request(request).await
+    }
+    
+    async fn begin_auth_session_via_credentials(
+        &self,
+        account_name: &str,
+        encrypted_password: &str,
+        encryption_timestamp: u64,
+    ) -> Result<BeginAuthSessionResponse> {
+        let uri = self.get_api_url("BeginAuthSessionViaCredentials");
+        let encryption_timestamp = encryption_timestamp.to_string();
+        let request = self.client.post(&uri)
+            .form(&[
+                ("account_name", account_name),
+                ("encrypted_password", encrypted_password),
+                ("encryption_timestamp", &encryption_timestamp),
+                ("remember_login", "true"),
+                ("platform_type", PLATFORM_TYPE_WEB_BROWSER),
+                ("persistence", SESSION_PERSISTENCE_PERSISTENT),
+                ("website_id", "Community"),
+                ("device_friendly_name", self.user_agent),
+            ]);
+        
+        self.send_api_request(request).await
+    }
+    
+    async fn update_auth_session_with_steam_guard_code(
+        &self,
+        client_id: u64,
+        steamid: u64,
+        code: &str,
+    ) -> Result<()> {
+        let uri = self.get_api_url("UpdateAuthSessionWithSteamGuardCode");
+        let request = self.client.post(&uri)
+            .form(&[
+                ("client_id", client_id.to_string().as_str()),
+                ("steamid", steamid.to_string().as_str()),
+                ("code", code),
+                ("code_type", CODE_TYPE_DEVICE_CODE),
+            ]);
+        
+        match self.send_api_request::<serde_json::Value>(request).await {
+            Ok(_) => Ok(()),
+            // The code was already accepted.
+            Err(Error::Auth(AuthError::EResult(ERESULT_DUPLICATE_REQUEST))) => Ok(()),
+            Err(error) => Err(error),
+        }
+    }
+    
+    /// Polls the auth session until it is approved or the poll timeout passes.
+    async fn poll_auth_session_status(
+        &self,
+        auth_session: &BeginAuthSessionResponse,
+    ) -> Result<PollAuthSessionResponse> {
+        let uri = self.get_api_url("PollAuthSessionStatus");
+        let interval = auth_session.interval
+            .unwrap_or(DEFAULT_POLL_INTERVAL_SECONDS)
+            // Prevents spamming requests.
+            .max(1.0);
+        let interval = std::time::Duration::from_secs_f64(interval);
+        let timeout = self.poll_timeout.to_std()
+            .unwrap_or_default();
+        let deadline = std::time::Instant::now() + timeout;
+        let mut client_id = auth_session.client_id;
+        
+        loop {
+            let request = self.client.post(&uri)
+                .form(&[
+                    ("client_id", client_id.to_string()),
+                    ("request_id", auth_session.request_id.clone()),
+                ]);
+            let response: PollAuthSessionResponse = self.send_api_request(request).await?;
+            
+            if response.refresh_token.is_some() {
+                return Ok(response);
+            }
+            
+            if let Some(new_client_id) = response.new_client_id {
+                client_id = new_client_id;
+            }
+            
+            if std::time::Instant::now() + interval > deadline {
+                return Err(AuthError::Timeout.into());
+            }
+            
+            async_std::task::sleep(interval).await;
+        }
+    }
+    
+    /// Finalizes the login and transfers it to the Steam community, returning its cookies.
+    async fn finalize_login(
+        &self,
+        refresh_token: &str,
+    ) -> Result<Vec<String>> {
+        let sessionid = generate_sessionid();
+        let uri = format!("{}/jwt/finalizelogin", self.login_url);
+        let form = Form::new()
+            .text("nonce", refresh_token.to_string())
+            .text("sessionid", sessionid.clone())
+            .text("redir", format!("{}/login/home/?goto=", self.community_url));
+        let response = self.with_origin(self.client.post(&uri))
+            .multipart(form)
+            .send()
+            .await?;
+        let status = response.status();
+        
+        if !status.is_success() {
+            return Err(Error::StatusCode(status));
+        }
+        
+        let body: FinalizeLoginResponse = response.json().await?;
+        
+        if let Some(code) = body.error {
+            return Err(AuthError::EResult(code).into());
+        }
+        
+        let steamid = body.steamid
+            .ok_or(Error::MalformedResponse("Missing steamID in finalize login response."))?;
+        let transfer = body.transfer_info
+            .into_iter()
+            .find(|transfer| transfer.url.starts_with(&self.community_url))
+            .ok_or(Error::MalformedResponse("Missing transfer for the Steam community."))?;
+        let form = transfer.params
+            .into_iter()
+            .fold(Form::new(), |form, (key, value)| match value {
+                serde_json::Value::String(value) => form.text(key, value),
+                value => form.text(key, value.to_string()),
+            })
+            .text("steamID", steamid);
+        let response = self.with_origin(self.client.post(&transfer.url))
+            .multipart(form)
+            .send()
+            .await?;
+        let status = response.status();
+        
+        if !status.is_success() {
+            return Err(Error::StatusCode(status));
+        }
+        
+        let mut cookies = helpers::get_set_cookies(response.headers());
+        
+        if !cookies.iter().any(|cookie| cookie.starts_with("steamLoginSecure=")) {
+            return Err(AuthError::MissingCookie("steamLoginSecure").into());
+        }
+        
+        cookies.retain(|cookie| !cookie.starts_with("sessionid="));
+        cookies.push(format!("sessionid={sessionid}"));
+        
+        Ok(cookies)
+    }
+    
+    /// Sends a request to the Steam Web API and checks the EResult of the response.
+    async fn send_api_request<T>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let response = self.with_origin(request)
+            .send()
+            .await?;
+        let eresult = response.headers()
+            .get("x-eresult")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u32>().ok());
+        
+        if let Some(code) = eresult {
+            if code != ERESULT_OK {
+                return Err(eresult_error(code).into());
+            }
+        }
+        
+        let status = response.status();
+        
+        if !status.is_success() {
+            return Err(Error::StatusCode(status));
+        }
+        
+        let body: ApiResponse<T> = response.json().await?;
+        
+        Ok(body.response)
+    }
+    
+    /// Adds the headers Steam expects from browser logins.
+    fn with_origin(
+        &self,
+        request: RequestBuilder,
+    ) -> RequestBuilder {
+        request
+            .header(ORIGIN, &self.community_url)
+            .header(REFERER, format!("{}/", self.community_url))
+    }
+    
+    fn get_api_url(
+        &self,
+        method: &str,
+    ) -> String {
+        format!("{}/IAuthenticationService/{method}/v1/", self.api_url)
+    }
+}
+
+/// Converts an EResult code into an [`AuthError`].
+fn eresult_error(code: u32) -> AuthError {
+    match code {
+        ERESULT_INVALID_PASSWORD => AuthError::InvalidPassword,
+        ERESULT_RATE_LIMIT_EXCEEDED => AuthError::RateLimited,
+        ERESULT_INVALID_LOGIN_AUTH_CODE |
+        ERESULT_TWO_FACTOR_CODE_MISMATCH => AuthError::InvalidSteamGuardCode,
+        code => AuthError::EResult(code),
+    }
+}
+
+impl From<AuthClientBuilder> for AuthClient {
+    fn from(builder: AuthClientBuilder) -> Self {
+        let client = builder.client
+            .unwrap_or_else(|| get_default_client(
+                Arc::new(Jar::default()),
+                builder.user_agent,
+            ));
+        
+        Self {
+            client,
+            user_agent: builder.user_agent,
+            api_url: builder.api_url,
+            login_url: builder.login_url,
+            community_url: builder.community_url,
+            time_offset: builder.time_offset,
+            poll_timeout: builder.poll_timeout,
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Raw responses from `IAuthenticationService`.
+
+use crate::serialize;
+use std::collections::HashMap;
+use serde::Deserialize;
+
+/// Wraps the body of a Steam Web API response.
+#[derive(Debug, Deserialize)]
+pub struct ApiResponse<T> {
+    pub response: T,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RsaKeyResponse {
+    pub publickey_mod: String,
+    pub publickey_exp: String,
+    #[serde(with = "serialize::string")]
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AllowedConfirmation {
+    pub confirmation_type: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BeginAuthSessionResponse {
+    #[serde(with = "serialize::string")]
+    pub client_id: u64,
+    pub request_id: String,
+    #[serde(default)]
+    pub interval: Option<f64>,
+    #[serde(default)]
+    pub allowed_confirmations: Vec<AllowedConfirmation>,
+    #[serde(with = "serialize::string")]
+    pub steamid: u64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PollAuthSessionResponse {
+    #[serde(default)]
+    #[serde(with = "serialize::option_string")]
+    pub new_client_id: Option<u64>,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default)]
+    pub access_token: Option<String>,
+    #[serde(default)]
+    pub account_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TransferInfo {
+    pub url: String,
+    #[serde(default)]
+    pub params: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FinalizeLoginResponse {
+    #[serde(default)]
+    pub error: Option<u32>,
+    #[serde(default)]
+    #[serde(rename = "steamID")]
+    pub steamid: Option<String>,
+    #[serde(default)]
+    pub transfer_info: Vec<TransferInfo>,
+}
@@ -0,0 +1,8 @@
+/// The outcome of confirming an offer.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ConfirmOutcome {
+    /// The offer was confirmed.
+    Confirmed,
+    /// The offer was already confirmed elsewhere, e.g. from the Steam mobile app.
+    AlreadyConfirmed,
+}
@@ -2,11 +2,12 @@ use strum::Display;
 use serde::{Deserialize, Serialize};
 
 /// The type of confirmation.
-#[derive(Debug, Serialize, Deserialize, Display, PartialEq, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Display, PartialEq, Clone, Copy, Default)]
 #[repr(u32)]
 #[serde(from = "u32")]
 pub enum ConfirmationType {
     /// Generic.
+    #[default]
     Generic = 1,
     /// Confirmation to confirm trade.
     Trade = 2,
@@ -18,12 +19,6 @@ pub enum ConfirmationType {
 	Unknown(u32),
 }
 
-impl Default for ConfirmationType {
-    fn default() -> Self {
-        Self::Generic
-    }
-}
-
 impl From<u32> for ConfirmationType {
     fn from(text: u32) -> Self {
         match text {
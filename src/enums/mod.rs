@@ -1,5 +1,6 @@
 //! Enumerated types.
 
+mod confirm_outcome;
 mod confirmation_method;
 mod confirmation_type;
 mod get_user_details_method;
@@ -8,6 +9,7 @@ mod offer_filter;
 mod trade_offer_state;
 mod trade_status;
 
+pub use confirm_outcome::ConfirmOutcome;
 pub use confirmation_method::ConfirmationMethod;
 pub use confirmation_type::ConfirmationType;
 pub use get_user_details_method::GetUserDetailsMethod;
@@ -56,16 +56,21 @@ pub enum Error {
     /// This trade offer has no confirmations.
     #[error("No confirmation for offer {}", .0)]
     NoConfirmationForOffer(TradeOfferId),
-    /// A confirmation could not be confirmed. If a message was contained in the response body it
-    /// will be included.
-    #[error(
-        "Confirmation unsuccessful. {}",
-        .0.as_ref().map(|s| s.as_str()).unwrap_or(
-            "The confirmation may have succeeded, the confirmation no longer exists, \
-or another trade may be going through. Check confirmations again to verify."
-        )
-    )]
-    ConfirmationUnsuccessful(Option<String>),
+    /// Cookies could not be set.
+    #[error("Error setting cookies: {}", .0)]
+    SetCookies(#[from] SetCookiesError),
+    /// Logging in failed.
+    #[error("Login error: {}", .0)]
+    Auth(#[from] AuthError),
+    /// The session expired hook failed to provide new cookies.
+    #[error("Session expired hook failed: {}", .0)]
+    SessionExpiredHook(AnyhowError),
+    /// A mobile confirmation request failed.
+    #[error("Confirmation error: {}", .0)]
+    Confirmation(#[from] ConfirmationError),
+    /// An error occurred when loading or saving data using a store.
+    #[error("Store error: {}", .0)]
+    Store(#[from] StoreError),
     /// The response is not expected. Check the contained message for more details.
     #[error("Malformed response: {}", .0)]
     MalformedResponse(&'static str),
@@ -75,10 +80,14 @@ or another trade may be going through. Check confirmations again to verify."
     /// A response from Steam returned an EResult code.
     #[error("Steam EResult error: {}\nRaw body:{}", .0, .1)]
     SteamEResult(u32, String),
+    /// Steam returned a list of offers that is likely incorrect, which usually happens when Steam
+    /// is having issues. This is transient and nothing was updated. Back off and try again later.
+    #[error("Steam returned a bogus offer list: {}", .0)]
+    BogusOfferList(&'static str),
 }
 
 /// Any number of issues with a provided parameter.
-#[derive(thiserror::Error, Debug)]
+#[derive(thiserror::Error, Debug, Clone)]
 pub enum ParameterError {
     /// An API key or JWT access token was expected but none was provided.
     #[error("No API key or access token provided. Make sure your API key or cookies are set.")]
@@ -86,6 +95,9 @@ pub enum ParameterError {
     /// No identity secret.
     #[error("No identity secret.")]
     NoIdentitySecret,
+    /// No refresh token.
+    #[error("No refresh token.")]
+    NoRefreshToken,
     /// Offer is missing trade ID.
     #[error(
         "Offer is missing trade ID. This usually means the offer it belongs to has not yet been \
@@ -132,6 +144,146 @@ pub enum FileError {
     SystemTime(#[from] std::time::SystemTimeError),
 }
 
+/// An error occurred when loading or saving data using a store.
+#[derive(thiserror::Error, Debug)]
+pub enum StoreError {
+    /// An error occurred when working with the file system.
+    #[error("{}", .0)]
+    File(#[from] FileError),
+    /// Data could not be serialized or deserialized.
+    #[error("Error serializing data: {}", .0)]
+    Serde(#[from] serde_json::Error),
+    /// Data could not be encrypted or decrypted. When loading, the key may not match the key the
+    /// data was saved with.
+    #[error("Data could not be encrypted or decrypted")]
+    Encryption,
+    /// An error occurred within SQLite.
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite error: {}", .0)]
+    Sqlite(#[from] rusqlite::Error),
+    /// Any other error. Useful for custom stores.
+    #[error("{}", .0)]
+    Other(#[from] AnyhowError),
+}
+
+/// An error reported to [`OfferHandler::on_handler_error`](crate::polling::OfferHandler::on_handler_error).
+#[derive(thiserror::Error, Debug)]
+pub enum HandlerError {
+    /// A handler method returned an error.
+    #[error("Handler method {} failed: {}", .method, .error)]
+    Failed {
+        /// The name of the method.
+        method: &'static str,
+        /// The error returned.
+        error: AnyhowError,
+    },
+    /// A handler method panicked.
+    #[error("Handler method {} panicked: {}", .method, .message)]
+    Panicked {
+        /// The name of the method.
+        method: &'static str,
+        /// The panic message.
+        message: String,
+    },
+    /// The handler fell behind and the contained number of polls were skipped.
+    #[error("Handler fell behind and skipped {} polls", .0)]
+    Lagged(u64),
+}
+
+/// An error encountered when logging in.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    /// The account name or password is incorrect.
+    #[error("Invalid account name or password")]
+    InvalidPassword,
+    /// There have been too many login attempts. Try again later.
+    #[error("Too many login attempts. Try again later.")]
+    RateLimited,
+    /// A Steam Guard code is required but no shared secret was provided.
+    #[error("A Steam Guard code is required but no shared secret was provided")]
+    SteamGuardCodeRequired,
+    /// The Steam Guard code was rejected. Check the shared secret and time offset.
+    #[error("Invalid Steam Guard code. Check the shared secret and time offset.")]
+    InvalidSteamGuardCode,
+    /// The login must be confirmed in a way that is not supported, e.g. by email. Contains the
+    /// confirmation types Steam allows.
+    #[error("Unsupported login confirmation types: {:?}", .0)]
+    UnsupportedConfirmation(Vec<u32>),
+    /// The auth session was not approved before the poll timeout.
+    #[error("Timed out waiting for the auth session to be approved")]
+    Timeout,
+    /// The password could not be encrypted.
+    #[error("Error encrypting password: {}", .0)]
+    EncryptPassword(String),
+    /// A token is missing from the response.
+    #[error("Missing {} in response", .0)]
+    MissingToken(&'static str),
+    /// A cookie is missing from the response.
+    #[error("Missing {} cookie in response", .0)]
+    MissingCookie(&'static str),
+    /// Steam responded with an EResult code.
+    #[error("Steam EResult error: {}", .0)]
+    EResult(u32),
+}
+
+/// An error received from a mobile confirmation request. Parsed from the `needauth`, `success`,
+/// `message` and `detail` fields of `/mobileconf` responses.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ConfirmationError {
+    /// Steam requires authentication for confirmations. The session has likely expired and new
+    /// cookies need to be set.
+    #[error("Not authenticated for confirmations. The session may have expired.")]
+    NeedsAuth,
+    /// Steam rejected the confirmation key. Either the identity secret is invalid or the clock is
+    /// too far from Steam's servers. If this persists after syncing the time offset, the identity
+    /// secret is likely invalid. Steam does not return a code for this, so it is detected from the
+    /// known messages Steam responds with.
+    #[error(
+        "Invalid authenticator. Check the identity secret and time offset.{}",
+        format_confirmation_detail(.message, .detail).unwrap_or_default()
+    )]
+    InvalidAuthenticator {
+        /// The message from the response.
+        message: Option<String>,
+        /// The detail from the response.
+        detail: Option<String>,
+    },
+    /// The request was unsuccessful for another reason. This is usually transient.
+    #[error(
+        "Confirmation unsuccessful.{}",
+        format_confirmation_detail(.message, .detail)
+            .unwrap_or_else(|| " The confirmation may have succeeded, the confirmation no \
+longer exists, or another trade may be going through. Check confirmations again to verify."
+                .into())
+    )]
+    Unsuccessful {
+        /// The message from the response.
+        message: Option<String>,
+        /// The detail from the response.
+        detail: Option<String>,
+    },
+}
+
+impl ConfirmationError {
+    /// Whether the error is likely transient and the request can be retried as is.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Unsuccessful { .. })
+    }
+}
+
+/// Formats the message and detail from a confirmation response for display.
+fn format_confirmation_detail(
+    message: &Option<String>,
+    detail: &Option<String>,
+) -> Option<String> {
+    match (message, detail) {
+        (Some(message), Some(detail)) => Some(format!(" {message} ({detail})")),
+        (Some(text), None) |
+        (None, Some(text)) => Some(format!(" {text}")),
+        (None, None) => None,
+    }
+}
+
 /// An error occurred when setting cookies.
 #[derive(thiserror::Error, Debug)]
 pub enum SetCookiesError {
@@ -147,7 +299,7 @@ pub enum SetCookiesError {
 }
 
 /// An error received from a response when sending or acting of trade offers.
-#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
 #[repr(u8)]
 pub enum TradeOfferError {
     /// An unknown error occurred. The contained string will contain additional information.
@@ -0,0 +1,253 @@
+//! Values and fake APIs shared by the tests and the mock Steam server.
+
+use crate::api::response::RawTradeOffer;
+use crate::enums::{ConfirmationMethod, ConfirmationType, TradeOfferState};
+use crate::response::Confirmation;
+use crate::time;
+use crate::types::TradeOfferId;
+#[cfg(test)]
+use crate::api::response::RawAsset;
+#[cfg(test)]
+use crate::types::ClassId;
+
+/// Creates an offer we sent without any items. All of its times are the current time.
+pub(crate) fn raw_offer(
+    tradeofferid: TradeOfferId,
+    trade_offer_state: TradeOfferState,
+) -> RawTradeOffer {
+    let now = time::get_server_time_now();
+    
+    RawTradeOffer {
+        tradeofferid,
+        tradeid: None,
+        accountid_other: 1,
+        message: None,
+        items_to_receive: Vec::new(),
+        items_to_give: Vec::new(),
+        is_our_offer: true,
+        from_real_time_trade: false,
+        expiration_time: now,
+        time_created: now,
+        time_updated: now,
+        trade_offer_state,
+        escrow_end_date: None,
+        confirmation_method: ConfirmationMethod::None,
+    }
+}
+
+/// Creates a single TF2 item with `classid`.
+#[cfg(test)]
+pub(crate) fn raw_asset(
+    classid: ClassId,
+) -> RawAsset {
+    RawAsset {
+        appid: 440,
+        contextid: 2,
+        assetid: 1,
+        classid,
+        instanceid: None,
+        amount: 1,
+        missing: false,
+    }
+}
+
+/// Creates a pending confirmation for the offer `creator_id`.
+pub(crate) fn confirmation(
+    id: u64,
+    creator_id: TradeOfferId,
+) -> Confirmation {
+    Confirmation {
+        id,
+        creator_id,
+        creation_time: time::get_server_time_now(),
+        nonce: 0,
+        cancel: "Cancel".into(),
+        accept: "Accept".into(),
+        multi: false,
+        r#type: ConfirmationType::Trade,
+        type_name: "Trade Offer".into(),
+        headline: "Trade".into(),
+        summary: Vec::new(),
+        icon: None,
+        warn: None,
+    }
+}
+
+#[cfg(test)]
+pub(crate) use fakes::{FakeApi, FakeConfirmationApi};
+
+#[cfg(test)]
+mod fakes {
+    use crate::SteamID;
+    use crate::api::TradeOfferApi;
+    use crate::api::request::GetTradeOffersOptions;
+    use crate::api::response::RawTradeOffer;
+    use crate::enums::GetUserDetailsMethod;
+    use crate::error::Result;
+    use crate::mobile_api::ConfirmationApi;
+    use crate::request::{GetTradeHistoryOptions, NewTradeOffer};
+    use crate::response::{
+        AcceptedOffer,
+        Asset,
+        ClassInfo,
+        Confirmation,
+        ConfirmationDetails,
+        SentOffer,
+        Trades,
+        UserDetails,
+    };
+    use crate::types::{AppId, ClassId, ClassInfoClass, ClassInfoMap, ContextId, TradeId, TradeOfferId};
+    use std::sync::{Arc, Mutex};
+    use async_trait::async_trait;
+    use tokio::sync::Notify;
+    
+    /// Responds with the offers it holds and records declined offers. Receipts are empty and are
+    /// returned once `receipt_ready` is notified.
+    #[derive(Debug, Default)]
+    pub(crate) struct FakeApi {
+        pub offers: Mutex<Vec<RawTradeOffer>>,
+        /// Classes without a classinfo. Every other classinfo is known.
+        pub missing_classids: Vec<ClassId>,
+        pub declined: Arc<Mutex<Vec<TradeOfferId>>>,
+        pub receipt_ready: Notify,
+    }
+    
+    #[async_trait]
+    impl TradeOfferApi for FakeApi {
+        async fn get_raw_trade_offers(
+            &self,
+            _options: &GetTradeOffersOptions,
+        ) -> Result<(Vec<RawTradeOffer>, Option<ClassInfoMap>)> {
+            Ok((self.offers.lock().unwrap().clone(), None))
+        }
+        
+        async fn get_asset_classinfos(
+            &self,
+            classes: &[ClassInfoClass],
+        ) -> Result<ClassInfoMap> {
+            Ok(classes
+                .iter()
+                .filter(|(_appid, classid, _instanceid)| !self.missing_classids.contains(classid))
+                .map(|class| (*class, Arc::new(ClassInfo::default())))
+                .collect())
+        }
+        
+        async fn get_trade_offer(
+            &self,
+            _tradeofferid: TradeOfferId,
+        ) -> Result<RawTradeOffer> {
+            unimplemented!()
+        }
+        
+        async fn send_offer(
+            &self,
+            _offer: &NewTradeOffer,
+            _counter_tradeofferid: Option<TradeOfferId>,
+        ) -> Result<SentOffer> {
+            unimplemented!()
+        }
+        
+        async fn accept_offer(
+            &self,
+            _tradeofferid: TradeOfferId,
+            _partner: SteamID,
+        ) -> Result<AcceptedOffer> {
+            unimplemented!()
+        }
+        
+        async fn decline_offer(
+            &self,
+            tradeofferid: TradeOfferId,
+        ) -> Result<TradeOfferId> {
+            self.declined.lock().unwrap().push(tradeofferid);
+            Ok(tradeofferid)
+        }
+        
+        async fn cancel_offer(
+            &self,
+            _tradeofferid: TradeOfferId,
+        ) -> Result<TradeOfferId> {
+            unimplemented!()
+        }
+        
+        async fn get_trade_history(
+            &self,
+            _options: &GetTradeHistoryOptions,
+        ) -> Result<Trades> {
+            unimplemented!()
+        }
+        
+        async fn get_receipt(
+            &self,
+            _tradeid: &TradeId,
+        ) -> Result<Vec<Asset>> {
+            self.receipt_ready.notified().await;
+            Ok(Vec::new())
+        }
+        
+        async fn get_inventory(
+            &self,
+            _steamid: SteamID,
+            _appid: AppId,
+            _contextid: ContextId,
+            _tradable_only: bool,
+        ) -> Result<Vec<Asset>> {
+            unimplemented!()
+        }
+        
+        async fn get_user_details(
+            &self,
+            _partner: SteamID,
+            _method: GetUserDetailsMethod,
+        ) -> Result<UserDetails> {
+            unimplemented!()
+        }
+        
+        async fn check_session(
+            &self,
+        ) -> Result<bool> {
+            unimplemented!()
+        }
+    }
+    
+    /// Responds with a confirmation for each offer it holds and records accepted confirmations.
+    #[derive(Debug, Default)]
+    pub(crate) struct FakeConfirmationApi {
+        pub tradeofferids: Vec<TradeOfferId>,
+        pub accepted: Mutex<Vec<TradeOfferId>>,
+    }
+    
+    #[async_trait]
+    impl ConfirmationApi for FakeConfirmationApi {
+        async fn get_trade_confirmations(
+            &self,
+        ) -> Result<Vec<Confirmation>> {
+            Ok(self.tradeofferids
+                .iter()
+                .map(|tradeofferid| super::confirmation(*tradeofferid, *tradeofferid))
+                .collect())
+        }
+        
+        async fn get_confirmation_details(
+            &self,
+            _confirmation: &Confirmation,
+        ) -> Result<ConfirmationDetails> {
+            unimplemented!()
+        }
+        
+        async fn accept_confirmation(
+            &self,
+            confirmation: &Confirmation,
+        ) -> Result<()> {
+            self.accepted.lock().unwrap().push(confirmation.creator_id);
+            Ok(())
+        }
+        
+        async fn cancel_confirmation(
+            &self,
+            _confirmation: &Confirmation,
+        ) -> Result<()> {
+            unimplemented!()
+        }
+    }
+}
@@ -17,6 +17,7 @@ use reqwest::header;
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
 use serde::de::{self, DeserializeOwned, Deserializer, MapAccess, Visitor};
 use serde_json::de::SliceRead;
+use url::Url;
 
 lazy_static! {
     pub static ref DEFAULT_CLIENT: HttpClient = {
@@ -30,7 +31,12 @@ lazy_static! {
 pub const USER_AGENT_STRING: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, \
 like Gecko) Chrome/97.0.4692.71 Safari/537.36";
 pub(crate) const COMMUNITY_HOSTNAME: &str = "steamcommunity.com";
-pub(crate) const WEB_API_HOSTNAME: &str = "api.steampowered.com";
+/// The default base URL for Steam community requests.
+pub(crate) const DEFAULT_COMMUNITY_URL: &str = "https://steamcommunity.com";
+/// The default base URL for Steam Web API requests.
+pub(crate) const DEFAULT_WEB_API_URL: &str = "https://api.steampowered.com";
+/// The default base URL for finalizing logins.
+pub(crate) const DEFAULT_LOGIN_URL: &str = "https://login.steampowered.com";
 
 #[derive(Debug, Clone)]
 pub struct CookiesData {
@@ -127,15 +133,21 @@ pub fn get_session_from_cookies(
         cookies.push(format!("sessionid={sessionid}"));
         sessionid
     };
-    let session = Session {
-        sessionid,
-        access_token,
-        steamid,
-    };
+    let session = Session::new(sessionid, access_token, steamid);
     
     Ok(session)
 }
 
+/// Checks that a base URL can be parsed and removes trailing slashes so paths can be appended.
+/// Panics if the URL is not valid.
+pub fn parse_base_url(url: String) -> String {
+    if let Err(error) = url.parse::<Url>() {
+        panic!("URL could not be parsed from {url}: {error}");
+    }
+    
+    url.trim_end_matches('/').to_string()
+}
+
 /// Writes a file atomically.
 pub async fn write_file_atomic(
     filepath: PathBuf,
@@ -323,6 +335,40 @@ pub async fn parses_response<D>(
 where
     D: DeserializeOwned,
 {
+    let bytes = check_response(response).await?;
+    
+    match serde_json::from_slice::<D>(&bytes) {
+        Ok(body) => Ok(body),
+        Err(_) => {
+            // unexpected response
+            let html = String::from_utf8_lossy(&bytes);
+            
+            check_html_for_errors(&html)?;
+            
+            Err(Error::MalformedResponseWithBody(
+                "Got unexpected non-JSON response.",
+                html.into()
+            ))
+        }
+    }
+}
+
+/// Checks an HTML response for errors and returns the page.
+pub async fn parses_html_response(
+    response: reqwest::Response,
+) -> Result<String, Error> {
+    let bytes = check_response(response).await?;
+    let html = String::from_utf8_lossy(&bytes).into_owned();
+    
+    check_html_for_errors(&html)?;
+    
+    Ok(html)
+}
+
+/// Checks the status, headers and body of a response for errors. Returns the body.
+async fn check_response(
+    response: reqwest::Response,
+) -> Result<Bytes, Error> {
     let status = response.status();
     let headers = response.headers().clone();
     let bytes = response.bytes().await?;
@@ -355,53 +401,47 @@ where
     // This doesn't return anything but will catch errors in the response.
     check_response_for_errors(&bytes, eresult)?;
     
-    match serde_json::from_slice::<D>(&bytes) {
-        Ok(body) => Ok(body),
-        Err(_) => {
-            // unexpected response
-            let html = String::from_utf8_lossy(&bytes);
-            
-            if html.contains(r#"<h1>Sorry!</h1>"#) {
-                return if let Some((
-                    _,
-                    message,
-                )) = regex_captures!("<h3>(.+)</h3>", &html) {
-                    Err(Error::UnexpectedResponse(message.into()))
-                } else {
-                    Err(Error::MalformedResponseWithBody(
-                        "Steam returned an HTML response but an error message could not be \
-                        detected (an <h3> tag was expected but was not found)",
-                        html.into()
-                    ))
-                };
-            }
-            
-            if html.contains(r#"<h1>Sign In</h1>"#) && html.contains(r#"g_steamID = false;"#) {
-                return Err(Error::NotLoggedIn);
-            }
-            
-            if regex_is_match!(r#"\{"success": ?false\}"#, &html) {
-                return Err(Error::ResponseUnsuccessful);
-            }
-    
-            // Session seems expired
-            if html.contains("Access is denied") {
-                return Err(Error::NotLoggedIn);
-            }
-            
-            if let Some((
-                _,
-                message,
-            )) = regex_captures!(r#"<div id="error_msg">\s*([^<]+)\s*</div>"#, &html) {
-                return Err(Error::TradeOffer(TradeOfferError::from(message)));
-            }
-            
+    Ok(bytes)
+}
+
+/// Checks an HTML page for error messages and login pages.
+fn check_html_for_errors(html: &str) -> Result<(), Error> {
+    if html.contains(r#"<h1>Sorry!</h1>"#) {
+        return if let Some((
+            _,
+            message,
+        )) = regex_captures!("<h3>(.+)</h3>", html) {
+            Err(Error::UnexpectedResponse(message.into()))
+        } else {
             Err(Error::MalformedResponseWithBody(
-                "Got unexpected non-JSON response.",
+                "Steam returned an HTML response but an error message could not be \
+                detected (an <h3> tag was expected but was not found)",
                 html.into()
             ))
-        }
+        };
+    }
+    
+    if html.contains(r#"<h1>Sign In</h1>"#) && html.contains(r#"g_steamID = false;"#) {
+        return Err(Error::NotLoggedIn);
+    }
+    
+    if regex_is_match!(r#"\{"success": ?false\}"#, html) {
+        return Err(Error::ResponseUnsuccessful);
+    }
+    
+    // Session seems expired
+    if html.contains("Access is denied") {
+        return Err(Error::NotLoggedIn);
+    }
+    
+    if let Some((
+        _,
+        message,
+    )) = regex_captures!(r#"<div id="error_msg">\s*([^<]+)\s*</div>"#, html) {
+        return Err(Error::TradeOffer(TradeOfferError::from(message)));
     }
+    
+    Ok(())
 }
 
 #[cfg(test)]
@@ -415,6 +455,17 @@ mod tests {
         assert_eq!(sessionid.len(), 24);
     }
     
+    #[test]
+    fn parses_base_url() {
+        assert_eq!(parse_base_url("http://127.0.0.1:8080/".into()), "http://127.0.0.1:8080");
+    }
+    
+    #[test]
+    #[should_panic]
+    fn panics_on_invalid_base_url() {
+        parse_base_url("127.0.0.1:8080".into());
+    }
+    
     #[test]
     fn deserializes_str_error_response() {
         let json = r#"{
@@ -0,0 +1,86 @@
+//! Decoding the JSON Web Tokens Steam uses for access and refresh tokens.
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde::{Deserialize, Deserializer};
+
+/// The claims of a JSON Web Token.
+#[derive(Debug, Clone, Deserialize)]
+pub struct JwtClaims {
+    /// The subject of the token. For Steam tokens this is the Steam ID the token was issued for.
+    #[serde(default)]
+    pub sub: Option<String>,
+    /// The unix timestamp the token expires at.
+    pub exp: i64,
+    /// The unix timestamp the token was issued at.
+    #[serde(default)]
+    pub iat: Option<i64>,
+    /// The audiences the token is valid for, e.g. `web:community`.
+    #[serde(default, deserialize_with = "string_or_vec")]
+    pub aud: Vec<String>,
+}
+
+/// Decodes the claims of a JSON Web Token. The signature is not verified.
+pub fn decode_jwt(token: &str) -> Option<JwtClaims> {
+    let payload = token.split('.').nth(1)?;
+    // Padding is not included in JWTs but is stripped in case it was added.
+    let bytes = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// The `aud` claim may be a single string or an array of strings.
+fn string_or_vec<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrVec {
+        String(String),
+        Vec(Vec<String>),
+    }
+    
+    Ok(match StringOrVec::deserialize(deserializer)? {
+        StringOrVec::String(audience) => vec![audience],
+        StringOrVec::Vec(audience) => audience,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    
+    /// Creates an unsigned token with the payload.
+    pub(crate) fn encode_jwt(payload: &str) -> String {
+        format!("eyJhbGciOiJFZERTQSJ9.{}.signature", URL_SAFE_NO_PAD.encode(payload))
+    }
+    
+    #[test]
+    fn decodes_claims() {
+        let token = encode_jwt(r#"{"sub":"76561198000000000","exp":1700000000}"#);
+        let claims = decode_jwt(&token).unwrap();
+        
+        assert_eq!(claims.sub.as_deref(), Some("76561198000000000"));
+        assert_eq!(claims.exp, 1700000000);
+        assert_eq!(claims.iat, None);
+        assert!(claims.aud.is_empty());
+        assert!(decode_jwt("not a token").is_none());
+    }
+    
+    #[test]
+    fn decodes_audience() {
+        let token = encode_jwt(concat!(
+            r#"{"sub":"76561198000000000","exp":1700000000,"iat":1699900000,"#,
+            r#""aud":["web","mobile"]}"#,
+        ));
+        let claims = decode_jwt(&token).unwrap();
+        
+        assert_eq!(claims.iat, Some(1699900000));
+        assert_eq!(claims.aud, vec!["web", "mobile"]);
+        
+        let token = encode_jwt(r#"{"sub":"76561198000000000","exp":1700000000,"aud":"web"}"#);
+        
+        assert_eq!(decode_jwt(&token).unwrap().aud, vec!["web"]);
+    }
+}
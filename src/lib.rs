@@ -6,11 +6,20 @@
 //! 
 //! - Richly-featured API for creating, accepting, cancelling, and declining trade offers.
 //! - Manages account trade offer state.
-//! - Mobile confirmations.
+//! - Mobile confirmations, with optional automatic syncing of the time offset from Steam's
+//!   servers.
 //! - Loading inventories.
 //! - Trade history.
 //! - Helper method for getting your Steam Web API key.
+//! - Logging in with account credentials and Steam Guard codes to obtain web cookies.
+//! - Optionally saves sessions to the data directory, encrypted, and restores them on startup.
 //! - Automatically cancels offers past a set duration during polls.
+//! - Pluggable storage for poll data (files by default, in-memory, or SQLite with the `sqlite`
+//!   feature).
+//! - In-process mock Steam server for testing offer lifecycles without an account (`test-util`
+//!   feature).
+//! - Receive poll events through a channel, any number of subscribers, or an event handler
+//!   trait.
 //! - Loads descriptions (classinfos) for assets. Classinfos are cached to file and read when
 //!   available. The manager holds a [Least frequently used
 //!   (LFU) cache](https://en.wikipedia.org/wiki/Least_frequently_used)
@@ -35,7 +44,11 @@ mod helpers;
 mod classinfo_cache;
 mod time;
 mod session;
+mod session_store;
+mod jwt;
 mod static_functions;
+#[cfg(any(test, feature = "test-util"))]
+mod fixtures;
 
 // Public modules
 pub mod error;
@@ -45,10 +58,15 @@ pub mod enums;
 pub mod types;
 pub mod api;
 pub mod mobile_api;
+pub mod auth;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 
 // Re-exports for convenience
 pub use static_functions::get_inventory;
 pub use classinfo_cache::ClassInfoCache;
+pub use session::SessionInfo;
+pub use session_store::{SessionStore, StoredSession};
 pub use manager::{TradeOfferManager, TradeOfferManagerBuilder};
 
 // Polling-related exports in a dedicated submodule
@@ -56,13 +74,33 @@ pub mod polling {
     //! Models related to polling trade offers.
     pub use super::manager::polling::{
         Poll,
+        PollEvent,
+        Receipt,
         Result,
         PollAction,
         PollType,
         PollOptions,
+        PollStatus,
+        ReceiptOptions,
+        CancelPolicy,
+        CancelFilter,
+        ConfirmPolicy,
+        ConfirmFilter,
+        OfferHandler,
+        HandlerOptions,
         PollReceiver,
         PollSender,
+        PollSubscriber,
+        ConfirmationEvent,
+        ConfirmationPollOptions,
+        ConfirmationReceiver,
+        PollData,
+        PollDataStore,
+        FilePollDataStore,
+        MemoryPollDataStore,
     };
+    #[cfg(feature = "sqlite")]
+    pub use super::manager::polling::SqlitePollDataStore;
 }
 
 // External crate re-exports
@@ -71,4 +109,5 @@ pub use reqwest_middleware;
 pub use chrono;
 pub use steamid_ng;
 pub use steamid_ng::SteamID;
+pub use async_trait::async_trait;
 pub use another_steam_totp::get_steam_server_time_offset;
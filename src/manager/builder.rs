@@ -1,13 +1,53 @@
 use super::TradeOfferManager;
+use super::session_renewer::SessionExpiredHook;
+use super::polling::PollDataStore;
 use crate::ClassInfoCache;
 use crate::enums::Language;
-use crate::helpers::{default_data_directory, USER_AGENT_STRING};
-use crate::api::DEFAULT_GET_INVENTORY_PAGE_SIZE;
+use crate::error::AnyhowError;
+use crate::helpers::{default_data_directory, parse_base_url, USER_AGENT_STRING};
+use crate::helpers::{DEFAULT_COMMUNITY_URL, DEFAULT_LOGIN_URL, DEFAULT_WEB_API_URL};
+use crate::api::{DEFAULT_GET_INVENTORY_PAGE_SIZE, SteamTradeOfferAPI, TradeOfferApi};
+use crate::mobile_api::{ConfirmationApi, MobileAPI, TimeSyncOptions};
+use crate::SteamID;
+use std::fmt;
+use std::future::Future;
 use std::path::PathBuf;
 use std::sync::Arc;
 use reqwest::cookie::Jar;
 use reqwest_middleware::ClientWithMiddleware;
 
+/// Creates the API used by the manager from the default API.
+pub(crate) struct WrapApi<D, T: ?Sized>(Arc<dyn Fn(D) -> Arc<T> + Send + Sync>);
+
+impl<D, T: ?Sized> WrapApi<D, T> {
+    /// Creates the API from the default API.
+    pub fn wrap(&self, api: D) -> Arc<T> {
+        (self.0)(api)
+    }
+}
+
+impl<D, T: ?Sized> Clone for WrapApi<D, T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<D, T: ?Sized> fmt::Debug for WrapApi<D, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("WrapApi")
+    }
+}
+
+/// The key sessions are encrypted with. Redacted from debug output.
+#[derive(Clone)]
+pub(crate) struct SessionStoreKey(pub [u8; 32]);
+
+impl fmt::Debug for SessionStoreKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SessionStoreKey(<redacted>)")
+    }
+}
+
 /// Builder for constructing a [`TradeOfferManager`].
 /// 
 /// An access token or API key is required to use the Steam Web API. If you provide an API key,
@@ -55,6 +95,27 @@ pub struct TradeOfferManagerBuilder {
     pub(crate) time_offset: i64,
     /// Cookies to set on initialization.
     pub(crate) cookies: Option<Vec<String>>,
+    /// The store for poll data. Defaults to a file store in the data directory.
+    pub(crate) poll_data_store: Option<Arc<dyn PollDataStore>>,
+    /// Options for automatically syncing the time offset. Not synced if not set.
+    pub(crate) time_sync: Option<TimeSyncOptions>,
+    /// The refresh token for renewing the session.
+    pub(crate) refresh_token: Option<String>,
+    /// The account to restore the session for and the key to encrypt it with. The session is
+    /// saved to the data directory.
+    pub(crate) session_store: Option<(SteamID, SessionStoreKey)>,
+    /// Called for new cookies when the session expires.
+    pub(crate) on_session_expired: Option<SessionExpiredHook>,
+    /// The base URL for Steam community requests.
+    pub(crate) community_url: String,
+    /// The base URL for Steam Web API requests.
+    pub(crate) api_url: String,
+    /// The base URL for finalizing logins when renewing the session.
+    pub(crate) login_url: String,
+    /// Creates the API used for trade offer requests from the default API.
+    pub(crate) trade_offer_api: Option<WrapApi<SteamTradeOfferAPI, dyn TradeOfferApi>>,
+    /// Creates the API used for mobile confirmation requests from the default API.
+    pub(crate) confirmation_api: Option<WrapApi<MobileAPI, dyn ConfirmationApi>>,
 }
 
 impl Default for TradeOfferManagerBuilder {
@@ -72,6 +133,16 @@ impl Default for TradeOfferManagerBuilder {
             user_agent: USER_AGENT_STRING,
             time_offset: 0,
             cookies: None,
+            poll_data_store: None,
+            time_sync: None,
+            refresh_token: None,
+            session_store: None,
+            on_session_expired: None,
+            community_url: DEFAULT_COMMUNITY_URL.into(),
+            api_url: DEFAULT_WEB_API_URL.into(),
+            login_url: DEFAULT_LOGIN_URL.into(),
+            trade_offer_api: None,
+            confirmation_api: None,
         }
     }
 }
@@ -98,7 +169,9 @@ impl TradeOfferManagerBuilder {
         self
     }
     
-    /// The data_directory is the directory used to store poll data and classinfo data.
+    /// The data_directory is the directory used to store poll data and classinfo data. Poll data
+    /// is not stored here if a different store is set using
+    /// [`TradeOfferManagerBuilder::poll_data_store`].
     pub fn data_directory<T>(mut self, data_directory: T) -> Self
     where
         T: Into<PathBuf>,
@@ -146,12 +219,153 @@ impl TradeOfferManagerBuilder {
         self
     }
     
+    /// Automatically syncs the time offset from Steam's servers when the manager is built, then
+    /// periodically and after confirmation keys are rejected. The offset set using
+    /// [`TradeOfferManagerBuilder::time_offset`] is used until the first sync completes. Requires
+    /// the manager to be built within a tokio runtime.
+    pub fn sync_time_offset(mut self, options: TimeSyncOptions) -> Self {
+        self.time_sync = Some(options);
+        self
+    }
+    
     /// The web cookies.
     pub fn cookies(mut self, cookies: Vec<String>) -> Self {
         self.cookies = Some(cookies);
         self
     }
     
+    /// The refresh token for renewing the session when the access token expires. If no cookies
+    /// are set, a session is created from the refresh token. Requires the manager to be built
+    /// within a tokio runtime. See [`TradeOfferManager::set_refresh_token`].
+    pub fn refresh_token(mut self, refresh_token: String) -> Self {
+        self.refresh_token = Some(refresh_token);
+        self
+    }
+    
+    /// Saves the session to the data directory, encrypted with `key`, so it does not need to be
+    /// set again after a restart. The session for `steamid` is restored on build, then saved
+    /// whenever cookies are set or the session is renewed. Cookies and a refresh token passed to
+    /// this builder take precedence over the restored session.
+    /// 
+    /// Use the same key every time. Sessions saved with a different key cannot be restored.
+    pub fn session_store(mut self, steamid: SteamID, key: [u8; 32]) -> Self {
+        self.session_store = Some((steamid, SessionStoreKey(key)));
+        self
+    }
+    
+    /// Called when the session expires and cannot be renewed using a refresh token, e.g. to log
+    /// in again. Returns fresh cookies which are set on the manager. Requests, confirmations and
+    /// polls that fail because we are not logged in are retried once after the cookies are set.
+    /// 
+    /// # Examples
+    /// ```no_run
+    /// use steam_tradeoffer_manager::TradeOfferManager;
+    /// use steam_tradeoffer_manager::auth::{AuthClient, Credentials};
+    /// 
+    /// let manager = TradeOfferManager::builder()
+    ///     .on_session_expired(|| async {
+    ///         let credentials = Credentials::new("account", "password");
+    ///         let session = AuthClient::builder().build().login(&credentials).await?;
+    ///         
+    ///         Ok(session.cookies)
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn on_session_expired<F, Fut>(mut self, on_session_expired: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Vec<String>, AnyhowError>> + Send + 'static,
+    {
+        self.on_session_expired = Some(SessionExpiredHook::new(on_session_expired));
+        self
+    }
+    
+    /// The base URL for Steam community requests, including the scheme and optionally a port e.g.
+    /// `http://127.0.0.1:8080`. Useful for testing against a local server. Defaults to
+    /// `https://steamcommunity.com`. Panics if the URL is not valid.
+    pub fn community_url<T>(mut self, community_url: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.community_url = parse_base_url(community_url.into());
+        self
+    }
+    
+    /// The base URL for Steam Web API requests, including the scheme and optionally a port e.g.
+    /// `http://127.0.0.1:8080`. Useful for testing against a local server. Defaults to
+    /// `https://api.steampowered.com`. Panics if the URL is not valid.
+    pub fn api_url<T>(mut self, api_url: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.api_url = parse_base_url(api_url.into());
+        self
+    }
+    
+    /// The base URL for finalizing logins when renewing the session using a refresh token,
+    /// including the scheme and optionally a port e.g. `http://127.0.0.1:8080`. Useful for
+    /// testing against a local server. Defaults to `https://login.steampowered.com`.
+    /// Panics if the URL is not valid.
+    pub fn login_url<T>(mut self, login_url: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.login_url = parse_base_url(login_url.into());
+        self
+    }
+    
+    /// The store used to load and save poll data. By default, poll data is saved as JSON files
+    /// in the data directory using a
+    /// [`FilePollDataStore`](crate::polling::FilePollDataStore).
+    pub fn poll_data_store<T>(mut self, store: T) -> Self
+    where
+        T: PollDataStore + 'static,
+    {
+        self.poll_data_store = Some(Arc::new(store));
+        self
+    }
+    
+    /// Sets the API used for trade offer, inventory and trade history requests. `wrap` is called
+    /// with the default [`SteamTradeOfferAPI`] when the manager is built and returns the API to
+    /// use, e.g. a fake for tests or a decorator around the default API. The default API is still
+    /// used for cookies and the session and is returned by [`TradeOfferManager::api`].
+    /// 
+    /// # Examples
+    /// ```
+    /// use steam_tradeoffer_manager::TradeOfferManager;
+    /// use steam_tradeoffer_manager::api::SteamTradeOfferAPI;
+    /// 
+    /// let manager = TradeOfferManager::builder()
+    ///     // Uses the default API as is.
+    ///     .trade_offer_api(|api: SteamTradeOfferAPI| api)
+    ///     .build();
+    /// ```
+    pub fn trade_offer_api<F, T>(mut self, wrap: F) -> Self
+    where
+        F: Fn(SteamTradeOfferAPI) -> T + Send + Sync + 'static,
+        T: TradeOfferApi + 'static,
+    {
+        self.trade_offer_api = Some(WrapApi(Arc::new(move |api| {
+            Arc::new(wrap(api)) as Arc<dyn TradeOfferApi>
+        })));
+        self
+    }
+    
+    /// Sets the API used for mobile confirmation requests. `wrap` is called with the default
+    /// [`MobileAPI`] when the manager is built and returns the API to use, e.g. a fake for tests
+    /// or a decorator around the default API. An identity secret is still required for
+    /// confirmations and the default API is returned by [`TradeOfferManager::mobile_api`].
+    pub fn confirmation_api<F, T>(mut self, wrap: F) -> Self
+    where
+        F: Fn(MobileAPI) -> T + Send + Sync + 'static,
+        T: ConfirmationApi + 'static,
+    {
+        self.confirmation_api = Some(WrapApi(Arc::new(move |mobile_api| {
+            Arc::new(wrap(mobile_api)) as Arc<dyn ConfirmationApi>
+        })));
+        self
+    }
+    
     /// Builds the [`TradeOfferManager`].
     pub fn build(self) -> TradeOfferManager {
         self.into()
@@ -1,16 +1,40 @@
 mod builder;
+mod session_renewer;
 pub(crate) mod polling;
 
 pub use builder::TradeOfferManagerBuilder;
-use polling::{Polling, PollOptions, PollReceiver, PollSender};
+use polling::{
+    Polling,
+    PollOptions,
+    PollReceiver,
+    PollAction,
+    PollSender,
+    PollSubscriber,
+    PollStatus,
+    PollDataStore,
+    FilePollDataStore,
+    OfferHandler,
+    HandlerOptions,
+    ConfirmationPollOptions,
+    ConfirmationReceiver,
+};
+
+use session_renewer::{is_logged_out, SessionRenewer};
 
 use crate::api::request::GetTradeOffersOptions;
-use crate::api::SteamTradeOfferAPI;
-use crate::enums::{TradeOfferState, OfferFilter, GetUserDetailsMethod};
-use crate::error::{Result, Error, ParameterError, SetCookiesError};
+use crate::api::{SteamTradeOfferAPI, TradeOfferApi};
+use crate::enums::{TradeOfferState, OfferFilter, GetUserDetailsMethod, ConfirmOutcome};
+use crate::auth::AuthClient;
+use crate::error::{
+    Result,
+    Error,
+    ParameterError,
+    SetCookiesError,
+    TradeOfferError,
+};
 use crate::helpers::get_default_client;
-use crate::mobile_api::MobileAPI;
-use crate::request::{NewTradeOffer, GetTradeHistoryOptions};
+use crate::mobile_api::{self, ConfirmationApi, MobileAPI, TimeSyncOptions};
+use crate::request::{NewTradeOffer, GetTradeHistoryOptions, ConfirmRetryOptions};
 use crate::response::{
     UserDetails,
     Asset,
@@ -18,16 +42,37 @@ use crate::response::{
     TradeOffer,
     AcceptedOffer,
     Confirmation,
+    ConfirmationDetails,
     Trades,
 };
 use crate::static_functions::get_api_key;
+use crate::session::SessionInfo;
+use crate::session_store::SessionStore;
 use crate::time;
 use crate::types::{AppId, ContextId, TradeOfferId};
 use crate::types::ServerTime;
+use std::future::Future;
 use std::sync::{Arc, Mutex, RwLock};
+use chrono::Duration;
+use tokio::sync::{broadcast, watch};
 use tokio_util::sync::{CancellationToken, DropGuard};
 use steamid_ng::SteamID;
 
+/// The number of polls buffered for each subscriber before they begin lagging.
+const POLL_SUBSCRIBER_CAPACITY: usize = 64;
+
+/// The polling task for offers and the handles for controlling it.
+#[derive(Debug)]
+struct PollingHandle {
+    cancellation_token: CancellationToken,
+    /// Cancels the polling task when dropped.
+    _drop_guard: DropGuard,
+    /// Sends actions to the polling task.
+    sender: PollSender,
+    /// Receives the status of the polling task.
+    status: watch::Receiver<PollStatus>,
+}
+
 /// Manager which includes functionality for interacting with trade offers, confirmations and
 /// inventories.
 #[derive(Debug, Clone)]
@@ -36,8 +81,28 @@ pub struct TradeOfferManager {
     api: SteamTradeOfferAPI,
     /// The underlying API for mobile confirmations.
     mobile_api: MobileAPI,
+    /// The API used for trade offer requests. This is the underlying API unless set using
+    /// [`TradeOfferManagerBuilder::trade_offer_api`].
+    trade_offer_api: Arc<dyn TradeOfferApi>,
+    /// The API used for mobile confirmation requests. This is the underlying mobile API unless
+    /// set using [`TradeOfferManagerBuilder::confirmation_api`].
+    confirmation_api: Arc<dyn ConfirmationApi>,
+    /// The store for poll data.
+    poll_data_store: Arc<dyn PollDataStore>,
     /// The task handle for polling offers.
-    polling: Arc<Mutex<Option<(CancellationToken, DropGuard)>>>,
+    polling: Arc<Mutex<Option<PollingHandle>>>,
+    /// Sends polls to subscribers.
+    poll_subscribers: broadcast::Sender<Arc<polling::Result>>,
+    /// The task handle for dispatching polls to the offer handler.
+    offer_handler: Arc<Mutex<Option<DropGuard>>>,
+    /// The task handle for polling confirmations.
+    confirmation_polling: Arc<Mutex<Option<(CancellationToken, DropGuard)>>>,
+    /// The task handle for syncing the time offset.
+    time_sync: Arc<Mutex<Option<DropGuard>>>,
+    /// Renews the session using the refresh token.
+    session_renewer: Arc<SessionRenewer>,
+    /// The task handle for renewing the session before it expires.
+    session_renewal: Arc<Mutex<Option<DropGuard>>>,
 }
 
 impl TradeOfferManager {
@@ -77,7 +142,8 @@ impl TradeOfferManager {
     /// Sets cookies.
     /// 
     /// Some features will only work if cookies are set, such as sending or responding to trade
-    /// offers. Make sure your cookies are set before calling these methods.
+    /// offers. Make sure your cookies are set before calling these methods. The cookies are
+    /// saved to the session store in the background, which requires a tokio runtime.
     /// 
     /// # Errors
     /// - If the cookies do not contain a `steamLoginSecure` cookie that includes an access token.
@@ -101,10 +167,74 @@ impl TradeOfferManager {
         cookies: Vec<String>,
     ) -> std::result::Result<(), SetCookiesError> {
         self.api.set_cookies(cookies.clone())?;
-        self.mobile_api.set_cookies(cookies)?;
+        self.mobile_api.set_cookies(cookies.clone())?;
+        self.session_renewer.spawn_save_session(Some(cookies));
         Ok(())
     }
     
+    /// Sets the refresh token from [`AuthClient::login`](crate::auth::AuthClient::login) used to
+    /// renew the session. New cookies are requested through `/jwt/finalizelogin` shortly before
+    /// the access token expires, and when a request fails because we are not logged in, in which
+    /// case the request is retried once. If no cookies are set, a session is created from the
+    /// refresh token.
+    /// 
+    /// Renewing and saving the session in the background require a tokio runtime.
+    pub fn set_refresh_token(
+        &self,
+        refresh_token: String,
+    ) {
+        let cancellation_token = CancellationToken::new();
+        
+        self.session_renewer.set_refresh_token(refresh_token);
+        self.session_renewer.spawn_save_session(None);
+        session_renewer::spawn_session_renewal(
+            Arc::clone(&self.session_renewer),
+            cancellation_token.clone(),
+        );
+        
+        // Dropping the previous guard stops the previous renewal task.
+        *self.session_renewal.lock().unwrap() = Some(cancellation_token.drop_guard());
+    }
+    
+    /// Gets the refresh token.
+    pub fn refresh_token(
+        &self,
+    ) -> Option<String> {
+        self.session_renewer.refresh_token()
+    }
+    
+    /// Renews the session using the refresh token and updates the cookies. Falls back to
+    /// [`TradeOfferManagerBuilder::on_session_expired`] if the access token cannot be renewed.
+    /// 
+    /// # Errors
+    /// - If no refresh token or session expired hook is set.
+    /// - Any other error encountered while performing requests.
+    pub async fn renew_session(
+        &self,
+    ) -> Result<()> {
+        self.session_renewer.renew(true).await
+    }
+    
+    /// Checks whether the session is still valid using a cheap authenticated request. Returns
+    /// `false` if the cookies are not set or no longer work. The session is not renewed.
+    pub async fn check_session(
+        &self,
+    ) -> Result<bool> {
+        if self.session_info().is_none() {
+            return Ok(false);
+        }
+        
+        self.trade_offer_api.check_session().await
+    }
+    
+    /// Gets information about the current session decoded from the access token, including when
+    /// it expires. [`None`] if you are not logged in.
+    pub fn session_info(
+        &self,
+    ) -> Option<SessionInfo> {
+        self.session_renewer.session_info()
+    }
+    
     /// Gets the logged-in user's [`SteamID`]. [`None`] if you are not logged in. Make sure your
     /// cookies are set.
     pub fn get_steamid(
@@ -113,19 +243,20 @@ impl TradeOfferManager {
         self.mobile_api.get_steamid().ok()
     }
     
-    /// Starts polling offers. Listen to the returned receiver for events. Messages can be sent to
-    /// the polling task using [`PollAction`](crate::polling::PollAction).
+    /// Starts polling offers. Listen to the returned receiver for events. Each poll contains a
+    /// [`PollEvent`](crate::polling::PollEvent) for every new or changed offer. Messages can be
+    /// sent to the polling task using [`PollAction`](crate::polling::PollAction).
     /// 
     /// Call [`TradeOfferManager::stop_polling`](crate::TradeOfferManager::stop_polling) to stop
-    /// polling offers. Polling will also stop if either the receiver or this [`TradeOfferManager`]
-    /// are dropped. If this method is called again, the previous polling task will be aborted and
-    /// a new one will be started.
+    /// polling offers. Polling will also stop if this [`TradeOfferManager`] is dropped, or if the
+    /// receiver is dropped while there are no subscribers (see
+    /// [`TradeOfferManager::subscribe_polls`]). If this method is called again, the previous
+    /// polling task will be aborted and a new one will be started.
     /// 
     /// # Examples
     /// ```no_run
     /// use steam_tradeoffer_manager::TradeOfferManager;
-    /// use steam_tradeoffer_manager::enums::TradeOfferState;
-    /// use steam_tradeoffer_manager::polling::{PollOptions, PollReceiver, Poll};
+    /// use steam_tradeoffer_manager::polling::{PollOptions, PollReceiver, Poll, PollEvent};
     /// 
     /// // Polls offers.
     /// async fn poll_offers(
@@ -145,19 +276,16 @@ impl TradeOfferManager {
     /// // Do something with offers.
     /// async fn on_poll(
     ///     manager: &TradeOfferManager,
-    ///     offers: Poll, // Poll is an alias for Vec<(TradeOffer, Option<TradeOfferState>)>
+    ///     events: Poll, // Poll is an alias for Vec<PollEvent>
     /// ) {
-    ///     for (mut offer, _old_state) in offers {
-    ///         let is_free_items = {
-    ///             // Offer must be active.
-    ///             offer.trade_offer_state == TradeOfferState::Active &&
-    ///             // Offer must not be created by us.
-    ///             !offer.is_our_offer && 
-    ///             // Offer must not be giving items.
-    ///             offer.items_to_give.is_empty()
+    ///     for event in events {
+    ///         // Only new offers sent to us.
+    ///         let PollEvent::NewReceivedOffer(mut offer) = event else {
+    ///             continue;
     ///         };
     ///         
-    ///         if is_free_items {
+    ///         // Offer must not be giving items.
+    ///         if offer.items_to_give.is_empty() {
     ///             println!("{offer} is giving us free items - accepting");
     ///             
     ///             match manager.accept_offer(&mut offer).await {
@@ -186,53 +314,303 @@ impl TradeOfferManager {
     /// ```
     /// 
     /// # Errors
-    /// - If the API key or an access token is not set.
+    /// - If [`TradeOfferApi::check_credentials`] fails, e.g. the API key or an access token is not
+    ///   set for the default API.
     /// - If the cookies are not set. (See [`TradeOfferManager::set_cookies`])
     pub fn start_polling(
         &self,
         options: PollOptions,
     ) -> Result<(PollSender, PollReceiver)> {
-        if self.api.api_key.is_none() && self.api.session.read().unwrap().is_none() {
-            return Err(ParameterError::MissingApiKeyOrAccessToken.into());
-        }
+        self.trade_offer_api.check_credentials()?;
         
         let steamid = self.get_steamid()
             .ok_or(Error::NotLoggedIn)?;
         let mut polling = self.polling.lock().unwrap();
         
-        if let Some((token, _)) = &*polling {
+        if let Some(handle) = &*polling {
             // Cancels the previous polling task.
-            token.cancel();
+            handle.cancellation_token.cancel();
         }
         
+        // Offers cannot be confirmed without an identity secret.
+        let confirmation_api = self.confirmation_api.check_credentials()
+            .ok()
+            .map(|()| Arc::clone(&self.confirmation_api));
         let Polling {
             sender,
             receiver,
             cancellation_token,
+            status,
         } = Polling::new(
             steamid,
-            self.api.clone(),
+            Arc::clone(&self.trade_offer_api),
+            confirmation_api,
+            Arc::clone(&self.poll_data_store),
+            Arc::clone(&self.session_renewer),
+            self.poll_subscribers.clone(),
             options,
         );
         let drop_guard = cancellation_token.clone().drop_guard();
         
-        *polling = Some((cancellation_token, drop_guard));
+        *polling = Some(PollingHandle {
+            cancellation_token,
+            _drop_guard: drop_guard,
+            sender: sender.clone(),
+            status,
+        });
         
         Ok((sender, receiver))
     }
     
+    /// Subscribes to polls. Any number of subscribers can receive polls alongside the receiver
+    /// returned by [`TradeOfferManager::start_polling`], and subscriptions carry over when polling
+    /// is restarted. Polling must be started using [`TradeOfferManager::start_polling`] for polls
+    /// to be received. If the receiver returned by `start_polling` is dropped, polling continues
+    /// as long as there are subscribers.
+    /// 
+    /// While the receiver returned by `start_polling` is in use it receives the original poll and
+    /// subscribers receive a copy. Errors cannot be cloned, so errors that are not a simple value,
+    /// such as errors parsing a response, are copied as [`Error::UnexpectedResponse`] containing
+    /// the message of the original error. Request errors with a status code are copied as
+    /// [`Error::StatusCode`].
+    /// 
+    /// # Examples
+    /// ```no_run
+    /// use steam_tradeoffer_manager::TradeOfferManager;
+    /// use steam_tradeoffer_manager::polling::PollOptions;
+    /// use tokio::sync::broadcast::error::RecvError;
+    /// 
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let manager = TradeOfferManager::builder()
+    ///         .api_key("00000000000000000000000000000000".to_string())
+    ///         .build();
+    ///     let mut subscriber = manager.subscribe_polls();
+    ///     let (_sender, _receiver) = manager.start_polling(PollOptions::default()).unwrap();
+    ///     
+    ///     loop {
+    ///         match subscriber.recv().await {
+    ///             Ok(poll) => match poll.as_ref() {
+    ///                 Ok(events) => println!("{} events", events.len()),
+    ///                 Err(error) => println!("Error encountered polling offers: {error}"),
+    ///             },
+    ///             Err(RecvError::Lagged(skipped)) => println!("Missed {skipped} polls"),
+    ///             Err(RecvError::Closed) => break,
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn subscribe_polls(
+        &self,
+    ) -> PollSubscriber {
+        self.poll_subscribers.subscribe()
+    }
+    
+    /// Registers a handler for events from polls, replacing any previously registered handler.
+    /// Events are dispatched from polls performed after polling is started using
+    /// [`TradeOfferManager::start_polling`]. See [`OfferHandler`] for an example.
+    pub fn set_offer_handler<H>(
+        &self,
+        handler: H,
+        options: HandlerOptions,
+    )
+    where
+        H: OfferHandler,
+    {
+        let cancellation_token = CancellationToken::new();
+        
+        tokio::spawn(polling::dispatch_polls(
+            Arc::new(handler),
+            self.subscribe_polls(),
+            options,
+            cancellation_token.clone(),
+        ));
+        
+        // Dropping the previous guard stops the previous handler.
+        *self.offer_handler.lock().unwrap() = Some(cancellation_token.drop_guard());
+    }
+    
+    /// Removes the registered offer handler.
+    pub fn remove_offer_handler(
+        &self,
+    ) {
+        self.offer_handler.lock().unwrap().take();
+    }
+    
     /// Stops polling.
     pub fn stop_polling(
         &self,
     ) {
         if let Ok(polling) = self.polling.lock() {
-            if let Some((token, _)) = &*polling {
+            if let Some(handle) = &*polling {
+                // Cancels the polling task.
+                handle.cancellation_token.cancel();
+            }
+        }
+    }
+    
+    /// Gets the status of the polling task, including the last poll time, last error and number
+    /// of consecutive failures. [`None`] if polling has not been started. The status of the last
+    /// polling task is returned after polling stops.
+    pub fn poll_status(
+        &self,
+    ) -> Option<PollStatus> {
+        self.polling.lock().unwrap()
+            .as_ref()
+            .map(|handle| handle.status.borrow().clone())
+    }
+    
+    /// Sets the duration to cancel an offer we sent after, taking precedence over the
+    /// [`CancelPolicy`](crate::polling::CancelPolicy). Call this after sending an offer to give
+    /// it its own duration. [`None`] removes a previously set duration.
+    /// 
+    /// If polling, the duration is sent to the polling task as
+    /// [`PollAction::SetCancelDuration`](crate::polling::PollAction::SetCancelDuration).
+    /// Otherwise it is saved to the poll data store and applies once polling starts.
+    /// 
+    /// # Errors
+    /// - If not polling and the cookies are not set. (See [`TradeOfferManager::set_cookies`])
+    /// - If not polling and the poll data could not be loaded or saved.
+    pub async fn set_cancel_duration(
+        &self,
+        tradeofferid: TradeOfferId,
+        duration: Option<Duration>,
+    ) -> Result<()> {
+        let sender = self.polling.lock().unwrap()
+            .as_ref()
+            .filter(|handle| !handle.cancellation_token.is_cancelled())
+            .map(|handle| handle.sender.clone());
+        
+        if let Some(sender) = sender {
+            let action = PollAction::SetCancelDuration {
+                tradeofferid,
+                duration,
+            };
+            
+            if sender.send(action).await.is_ok() {
+                return Ok(());
+            }
+            
+            // The polling task has stopped.
+        }
+        
+        let steamid = self.get_steamid()
+            .ok_or(Error::NotLoggedIn)?;
+        let mut poll_data = self.poll_data_store.load(steamid).await?
+            .unwrap_or_default();
+        
+        poll_data.set_cancel_duration(tradeofferid, duration);
+        self.poll_data_store.save(steamid, &poll_data).await?;
+        Ok(())
+    }
+    
+    /// Starts polling confirmations in the background. Listen to the returned receiver for
+    /// events for new and vanished confirmations of every type, and for failures fetching them.
+    /// 
+    /// Call [`TradeOfferManager::stop_confirmation_polling`] to stop polling confirmations.
+    /// Polling will also stop if either the receiver or this [`TradeOfferManager`] are dropped. If
+    /// this method is called again, the previous polling task will be aborted and a new one will
+    /// be started.
+    /// 
+    /// # Examples
+    /// ```no_run
+    /// use steam_tradeoffer_manager::TradeOfferManager;
+    /// use steam_tradeoffer_manager::polling::{ConfirmationEvent, ConfirmationPollOptions};
+    /// 
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let manager = TradeOfferManager::builder()
+    ///         .identity_secret("secret".to_string())
+    ///         .build();
+    ///     let mut receiver = manager
+    ///         .start_confirmation_polling(ConfirmationPollOptions::default())
+    ///         .unwrap();
+    ///     
+    ///     while let Some(event) = receiver.recv().await {
+    ///         match event {
+    ///             ConfirmationEvent::New(confirmation) => println!("New: {confirmation}"),
+    ///             ConfirmationEvent::Vanished(confirmation) => println!("Gone: {confirmation}"),
+    ///             ConfirmationEvent::Failed(error) => println!("Error: {error}"),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    /// 
+    /// # Errors
+    /// - If [`ConfirmationApi::check_credentials`] fails, e.g. the identity secret is not set for
+    ///   the default API.
+    /// - If the cookies are not set. (See [`TradeOfferManager::set_cookies`])
+    pub fn start_confirmation_polling(
+        &self,
+        options: ConfirmationPollOptions,
+    ) -> Result<ConfirmationReceiver> {
+        self.confirmation_api.check_credentials()?;
+        self.get_steamid()
+            .ok_or(Error::NotLoggedIn)?;
+        
+        let mut confirmation_polling = self.confirmation_polling.lock().unwrap();
+        
+        if let Some((token, _)) = &*confirmation_polling {
+            // Cancels the previous polling task.
+            token.cancel();
+        }
+        
+        let cancellation_token = CancellationToken::new();
+        let receiver = polling::spawn_confirmation_poller(
+            Arc::clone(&self.confirmation_api),
+            Arc::clone(&self.session_renewer),
+            options,
+            cancellation_token.clone(),
+        );
+        let drop_guard = cancellation_token.clone().drop_guard();
+        
+        *confirmation_polling = Some((cancellation_token, drop_guard));
+        
+        Ok(receiver)
+    }
+    
+    /// Stops polling confirmations.
+    pub fn stop_confirmation_polling(
+        &self,
+    ) {
+        if let Ok(confirmation_polling) = self.confirmation_polling.lock() {
+            if let Some((token, _)) = &*confirmation_polling {
                 // Cancels the polling task.
                 token.cancel();
             }
         }
     }
     
+    /// Starts syncing the time offset from Steam's servers in the background. The offset is synced
+    /// immediately, then every [`TimeSyncOptions::sync_interval`] and after confirmation requests
+    /// fail in a way that may be caused by clock drift. If this method is called again, the
+    /// previous sync task will be stopped and a new one will be started.
+    /// 
+    /// Syncing stops when [`TradeOfferManager::stop_time_sync`] is called or this
+    /// [`TradeOfferManager`] is dropped.
+    pub fn start_time_sync(
+        &self,
+        options: TimeSyncOptions,
+    ) {
+        let cancellation_token = CancellationToken::new();
+        
+        mobile_api::spawn_time_sync(
+            self.mobile_api.clone(),
+            options,
+            cancellation_token.clone(),
+        );
+        
+        // Dropping the previous guard stops the previous sync task.
+        *self.time_sync.lock().unwrap() = Some(cancellation_token.drop_guard());
+    }
+    
+    /// Stops syncing the time offset.
+    pub fn stop_time_sync(
+        &self,
+    ) {
+        self.time_sync.lock().unwrap().take();
+    }
+    
     /// Accepts an offer. Updates the state of the offer upon success as long as it does not
     /// require mobile confirmation.
     /// 
@@ -256,7 +634,9 @@ impl TradeOfferManager {
             );
         }
         
-        let accepted_offer = self.api.accept_offer(offer.tradeofferid, offer.partner).await?;
+        let accepted_offer = self.with_session_renewal(|| {
+            self.trade_offer_api.accept_offer(offer.tradeofferid, offer.partner)
+        }).await?;
         
         // This offer doesn't need confirmation, so we can update its state here. If the
         // accepted_offer returns without error and does not need confirmation, then we can
@@ -281,7 +661,7 @@ impl TradeOfferManager {
             return Err(ParameterError::CannotCancelOfferWeDidNotCreate.into());
         }
         
-        self.api.cancel_offer(offer.tradeofferid).await?;
+        self.with_session_renewal(|| self.trade_offer_api.cancel_offer(offer.tradeofferid)).await?;
         offer.trade_offer_state = TradeOfferState::Canceled;
         
         Ok(())
@@ -300,7 +680,7 @@ impl TradeOfferManager {
             return Err(ParameterError::CannotDeclineOfferWeCreated.into());
         }
         
-        self.api.decline_offer(offer.tradeofferid).await?;
+        self.with_session_renewal(|| self.trade_offer_api.decline_offer(offer.tradeofferid)).await?;
         offer.trade_offer_state = TradeOfferState::Declined;
         
         Ok(())
@@ -311,7 +691,30 @@ impl TradeOfferManager {
         &self,
         offer: &NewTradeOffer,
     ) -> Result<SentOffer> {
-        self.api.send_offer(offer, None).await
+        self.with_session_renewal(|| self.trade_offer_api.send_offer(offer, None)).await
+    }
+    
+    /// Sends an offer and confirms it if it needs mobile confirmation, retrying until the
+    /// confirmation appears. See [`TradeOfferManager::confirm_offer_id_with_retry`]. Returns the
+    /// sent offer and the outcome of confirming it, which is [`None`] if the offer did not need
+    /// confirmation.
+    /// 
+    /// # Errors
+    /// - If the offer could not be sent.
+    /// - If the offer could not be confirmed. The offer was still sent.
+    pub async fn send_and_confirm(
+        &self,
+        offer: &NewTradeOffer,
+        options: ConfirmRetryOptions,
+    ) -> Result<(SentOffer, Option<ConfirmOutcome>)> {
+        let sent_offer = self.send_offer(offer).await?;
+        let outcome = if sent_offer.needs_mobile_confirmation {
+            Some(self.confirm_offer_id_with_retry(sent_offer.tradeofferid, options).await?)
+        } else {
+            None
+        };
+        
+        Ok((sent_offer, outcome))
     }
     
     /// Counters an existing offer. This updates the state of the offer upon success.
@@ -320,10 +723,10 @@ impl TradeOfferManager {
         offer: &mut TradeOffer,
         counter_offer: &NewTradeOffer,
     ) -> Result<SentOffer> {
-        let sent_offer = self.api.send_offer(
+        let sent_offer = self.with_session_renewal(|| self.trade_offer_api.send_offer(
             counter_offer,
             Some(offer.tradeofferid),
-        ).await?;
+        )).await?;
         
         offer.trade_offer_state = TradeOfferState::Countered;
         
@@ -345,7 +748,9 @@ impl TradeOfferManager {
         let steamid = self.get_steamid()
             .ok_or(Error::NotLoggedIn)?;
         
-        self.api.get_inventory(steamid, appid, contextid, tradable_only).await
+        self.with_session_renewal(|| {
+            self.trade_offer_api.get_inventory(steamid, appid, contextid, tradable_only)
+        }).await
     }
     
     /// Gets a user's inventory.
@@ -358,7 +763,9 @@ impl TradeOfferManager {
         contextid: ContextId,
         tradable_only: bool,
     ) -> Result<Vec<Asset>> {
-        self.api.get_inventory(steamid, appid, contextid, tradable_only).await
+        self.with_session_renewal(|| {
+            self.trade_offer_api.get_inventory(steamid, appid, contextid, tradable_only)
+        }).await
     }
     
     /// Gets escrow details for a user. The `method` for obtaining details can be a `tradeofferid`
@@ -401,14 +808,18 @@ impl TradeOfferManager {
     where
         T: Into<GetUserDetailsMethod>,
     {
-        self.api.get_user_details(partner, method).await
+        let method = method.into();
+        
+        self.with_session_renewal(|| {
+            self.trade_offer_api.get_user_details(partner, method.clone())
+        }).await
     }
     
     /// Gets trade confirmations.
     pub async fn get_trade_confirmations(
         &self,
     ) -> Result<Vec<Confirmation>> {
-        self.mobile_api.get_trade_confirmations().await
+        self.with_session_renewal(|| self.confirmation_api.get_trade_confirmations()).await
     }
     
     /// Confirms a trade offer.
@@ -449,12 +860,81 @@ impl TradeOfferManager {
         Err(Error::NoConfirmationForOffer(tradeofferid))
     }
     
+    /// Confirms a trade offer using its ID, retrying with backoff until the confirmation appears.
+    /// Confirmations for newly sent offers often take a moment to appear.
+    /// 
+    /// While the confirmation is missing, the offer is checked to detect whether it was already
+    /// confirmed elsewhere, in which case [`ConfirmOutcome::AlreadyConfirmed`] is returned.
+    /// 
+    /// # Errors
+    /// - [`Error::NoConfirmationForOffer`] if the confirmation does not appear before the timeout.
+    /// - [`TradeOfferError::InvalidState`] if the offer is no longer awaiting confirmation and was
+    ///   not confirmed, e.g. it was cancelled.
+    /// - Any other error encountered while performing requests.
+    pub async fn confirm_offer_id_with_retry(
+        &self,
+        tradeofferid: TradeOfferId,
+        options: ConfirmRetryOptions,
+    ) -> Result<ConfirmOutcome> {
+        let timeout = options.timeout.to_std()
+            .unwrap_or_default();
+        let deadline = std::time::Instant::now() + timeout;
+        let mut delay = options.initial_delay;
+        
+        loop {
+            let confirmation = self.get_trade_confirmations().await?
+                .into_iter()
+                .find(|confirmation| confirmation.creator_id == tradeofferid);
+            
+            if let Some(confirmation) = confirmation {
+                self.accept_confirmation(&confirmation).await?;
+                return Ok(ConfirmOutcome::Confirmed);
+            }
+            
+            // The confirmation may be missing because the offer was confirmed elsewhere.
+            let offer = self.with_session_renewal(|| {
+                self.trade_offer_api.get_trade_offer(tradeofferid)
+            }).await?;
+            
+            match offer.trade_offer_state {
+                TradeOfferState::CreatedNeedsConfirmation => {},
+                TradeOfferState::Active |
+                TradeOfferState::Accepted |
+                TradeOfferState::InEscrow => return Ok(ConfirmOutcome::AlreadyConfirmed),
+                _ => return Err(Error::TradeOffer(TradeOfferError::InvalidState)),
+            }
+            
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            
+            if remaining.is_zero() {
+                return Err(Error::NoConfirmationForOffer(tradeofferid));
+            }
+            
+            let sleep_duration = delay.to_std()
+                .unwrap_or_default()
+                .min(remaining);
+            
+            async_std::task::sleep(sleep_duration).await;
+            delay = options.next_delay(delay);
+        }
+    }
+    
+    /// Gets the details for a confirmation. See [`MobileAPI::get_confirmation_details`].
+    pub async fn get_confirmation_details(
+        &self,
+        confirmation: &Confirmation,
+    ) -> Result<ConfirmationDetails> {
+        self.with_session_renewal(|| {
+            self.confirmation_api.get_confirmation_details(confirmation)
+        }).await
+    }
+    
     /// Accepts a confirmation.
     pub async fn accept_confirmation(
         &self,
         confirmation: &Confirmation,
     ) -> Result<()> {
-        self.mobile_api.accept_confirmation(confirmation).await
+        self.with_session_renewal(|| self.confirmation_api.accept_confirmation(confirmation)).await
     }
     
     /// Accepts confirmations.
@@ -463,18 +943,40 @@ impl TradeOfferManager {
         confirmations: &[Confirmation],
     ) -> Result<()> {
         for confirmation in confirmations {
-            self.mobile_api.accept_confirmation(confirmation).await?
+            self.accept_confirmation(confirmation).await?
         }
         
         Ok(())
     }
     
+    /// Accepts confirmations. Confirmations that can be confirmed together are sent in a single
+    /// request. See [`MobileAPI::accept_confirmations`].
+    /// 
+    /// Returns a result for each confirmation in the same order as `confirmations`.
+    pub async fn accept_confirmations_batch(
+        &self,
+        confirmations: &[Confirmation],
+    ) -> Vec<Result<()>> {
+        self.send_confirmations_with_session_renewal(confirmations, true).await
+    }
+    
     /// Cancels a confirmation.
     pub async fn cancel_confirmation(
         &self,
         confirmation: &Confirmation,
     ) -> Result<()> {
-        self.mobile_api.cancel_confirmation(confirmation).await
+        self.with_session_renewal(|| self.confirmation_api.cancel_confirmation(confirmation)).await
+    }
+    
+    /// Cancels confirmations. Confirmations that can be cancelled together are sent in a single
+    /// request. See [`MobileAPI::cancel_confirmations`].
+    /// 
+    /// Returns a result for each confirmation in the same order as `confirmations`.
+    pub async fn cancel_confirmations_batch(
+        &self,
+        confirmations: &[Confirmation],
+    ) -> Vec<Result<()>> {
+        self.send_confirmations_with_session_renewal(confirmations, false).await
     }
     
     /// Gets the trade receipt (new items) upon completion of a trade.
@@ -492,7 +994,7 @@ impl TradeOfferManager {
         } else if offer.items_to_receive.is_empty() {
             Ok(Vec::new())
         } else if let Some(tradeid) = offer.tradeid {
-            self.api.get_receipt(&tradeid).await
+            self.with_session_renewal(|| self.trade_offer_api.get_receipt(&tradeid)).await
         } else {
             Err(ParameterError::MissingTradeId.into())
         }
@@ -503,7 +1005,9 @@ impl TradeOfferManager {
         &self,
         offer: &mut TradeOffer,
     ) -> Result<()> {
-        let updated = self.api.get_trade_offer(offer.tradeofferid).await?;
+        let updated = self.with_session_renewal(|| {
+            self.trade_offer_api.get_trade_offer(offer.tradeofferid)
+        }).await?;
         
         offer.tradeofferid = updated.tradeofferid;
         offer.tradeid = updated.tradeid;
@@ -536,13 +1040,16 @@ impl TradeOfferManager {
         filter: OfferFilter,
         historical_cutoff: Option<ServerTime>,
     ) -> Result<Vec<TradeOffer>> {
-        let offers = self.api.get_trade_offers(&GetTradeOffersOptions {
+        let options = GetTradeOffersOptions {
             active_only: filter == OfferFilter::ActiveOnly,
             historical_only: filter == OfferFilter::HistoricalOnly,
             get_sent_offers: true,
             get_received_offers: true,
             get_descriptions: false,
             historical_cutoff,
+        };
+        let offers = self.with_session_renewal(|| {
+            self.trade_offer_api.get_trade_offers(&options)
         }).await?;
         
         // trim responses since these don't always return what we want
@@ -570,15 +1077,73 @@ impl TradeOfferManager {
         &self,
         options: &GetTradeHistoryOptions,
     ) -> Result<Trades> {
-        self.api.get_trade_history(options).await
+        self.with_session_renewal(|| self.trade_offer_api.get_trade_history(options)).await
+    }
+    
+    /// Performs a request. If it fails because we are not logged in and a refresh token or
+    /// session expired hook is set, the session is renewed and the request is retried once.
+    async fn with_session_renewal<T, F, Fut>(
+        &self,
+        request: F,
+    ) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        self.session_renewer.with_renewal(request).await
     }
     
-    /// Gets a reference to the underlying API.
+    /// Accepts or cancels confirmations in batches. If any fail because we are not logged in and
+    /// the session can be renewed, the session is renewed and those confirmations are retried
+    /// once.
+    async fn send_confirmations_with_session_renewal(
+        &self,
+        confirmations: &[Confirmation],
+        accept: bool,
+    ) -> Vec<Result<()>> {
+        let send = |confirmations: Vec<Confirmation>| async move {
+            if accept {
+                self.confirmation_api.accept_confirmations(&confirmations).await
+            } else {
+                self.confirmation_api.cancel_confirmations(&confirmations).await
+            }
+        };
+        let mut results = send(confirmations.to_vec()).await;
+        
+        if !results.iter().any(is_logged_out) || !self.session_renewer.can_renew() {
+            return results;
+        }
+        
+        if let Err(error) = self.session_renewer.renew(false).await {
+            log::warn!("Failed to renew session after confirmations failed: {error}");
+            return results;
+        }
+        
+        let (
+            indices,
+            retry,
+        ): (Vec<_>, Vec<_>) = results
+            .iter()
+            .enumerate()
+            .filter(|(_index, result)| is_logged_out(result))
+            .map(|(index, _result)| (index, confirmations[index].clone()))
+            .unzip();
+        
+        for (index, result) in indices.into_iter().zip(send(retry).await) {
+            results[index] = result;
+        }
+        
+        results
+    }
+    
+    /// Gets a reference to the underlying API. This is the default API, even if the API for
+    /// requests was set using [`TradeOfferManagerBuilder::trade_offer_api`].
     pub fn api(&self) -> &SteamTradeOfferAPI {
         &self.api
     }
     
-    /// Gets a reference to the underlying mobile API.
+    /// Gets a reference to the underlying mobile API. This is the default API, even if the API for
+    /// confirmations was set using [`TradeOfferManagerBuilder::confirmation_api`].
     pub fn mobile_api(&self) -> &MobileAPI {
         &self.mobile_api
     }
@@ -594,12 +1159,20 @@ impl From<TradeOfferManagerBuilder> for TradeOfferManager {
                 builder.user_agent,
             ));
         let classinfo_cache = builder.classinfo_cache.unwrap_or_default();
+        let poll_data_store = builder.poll_data_store
+            .unwrap_or_else(|| Arc::new(FilePollDataStore::new(builder.data_directory.clone())));
+        let session_store = builder.session_store
+            .map(|(steamid, key)| {
+                (steamid, SessionStore::new(builder.data_directory.clone(), key.0))
+            });
         let mut api_builder = SteamTradeOfferAPI::builder()
             .data_directory(builder.data_directory)
             .client(client.clone(), Arc::clone(&cookies))
             .language(builder.language)
             .get_inventory_page_size(builder.get_inventory_page_size)
-            .classinfo_cache(classinfo_cache);
+            .classinfo_cache(classinfo_cache)
+            .community_url(builder.community_url.clone())
+            .api_url(builder.api_url.clone());
         let session = Arc::new(RwLock::new(None));
         
         if let Some(api_key) = builder.api_key {
@@ -613,25 +1186,88 @@ impl From<TradeOfferManagerBuilder> for TradeOfferManager {
         api_builder = api_builder.session(Arc::clone(&session));
         
         let mut mobile_api_builder = MobileAPI::builder()
-            .client(client, cookies)
+            .client(client.clone(), cookies)
             .time_offset(builder.time_offset)
+            .community_url(builder.community_url.clone())
             .session(session);
         
         if let Some(identity_secret) = builder.identity_secret {
             mobile_api_builder = mobile_api_builder.identity_secret(identity_secret);
         }
         
+        let api = api_builder.build();
+        let mobile_api = mobile_api_builder.build();
+        let auth = AuthClient::builder()
+            .client(client)
+            .user_agent(builder.user_agent)
+            .time_offset(builder.time_offset)
+            .community_url(builder.community_url)
+            .api_url(builder.api_url)
+            .login_url(builder.login_url)
+            .build();
+        let session_renewer = SessionRenewer::new(
+            auth,
+            api.clone(),
+            mobile_api.clone(),
+            session_store.as_ref().map(|(_steamid, store)| store.clone()),
+            builder.on_session_expired,
+        );
+        let trade_offer_api = match &builder.trade_offer_api {
+            Some(wrap_api) => wrap_api.wrap(api.clone()),
+            None => Arc::new(api.clone()),
+        };
+        let confirmation_api = match &builder.confirmation_api {
+            Some(wrap_api) => wrap_api.wrap(mobile_api.clone()),
+            None => Arc::new(mobile_api.clone()),
+        };
         let manager = Self {
-            api: api_builder.build(),
-            mobile_api: mobile_api_builder.build(),
+            api,
+            mobile_api,
+            trade_offer_api,
+            confirmation_api,
+            poll_data_store,
             polling: Arc::new(Mutex::new(None)),
+            poll_subscribers: broadcast::channel(POLL_SUBSCRIBER_CAPACITY).0,
+            offer_handler: Arc::new(Mutex::new(None)),
+            confirmation_polling: Arc::new(Mutex::new(None)),
+            time_sync: Arc::new(Mutex::new(None)),
+            session_renewer: Arc::new(session_renewer),
+            session_renewal: Arc::new(Mutex::new(None)),
         };
         
-        if let Some(cookies) = builder.cookies {
+        if let Some(options) = builder.time_sync {
+            if tokio::runtime::Handle::try_current().is_ok() {
+                manager.start_time_sync(options);
+            } else {
+                log::warn!("Time offset not synced: the manager was not built in a tokio runtime");
+            }
+        }
+        
+        let stored_session = session_store.and_then(|(steamid, store)| {
+            store.load(steamid)
+                .inspect_err(|error| log::warn!("Failed to restore session: {error}"))
+                .ok()
+                .flatten()
+        });
+        let (stored_cookies, stored_refresh_token) = stored_session
+            .map(|session| (Some(session.cookies), session.refresh_token))
+            .unwrap_or_default();
+        
+        if let Some(cookies) = builder.cookies.or(stored_cookies) {
             // We don't care if this fails.
             manager.set_cookies(cookies).ok();
         }
         
+        if let Some(refresh_token) = builder.refresh_token.or(stored_refresh_token) {
+            if tokio::runtime::Handle::try_current().is_ok() {
+                manager.set_refresh_token(refresh_token);
+            } else {
+                log::warn!("Session not renewed: the manager was not built in a tokio runtime");
+                manager.session_renewer.set_refresh_token(refresh_token);
+                manager.session_renewer.spawn_save_session(None);
+            }
+        }
+        
         manager
     }
 }
@@ -0,0 +1,158 @@
+use crate::api::response::RawTradeOffer;
+use crate::enums::TradeOfferState;
+use crate::response::TradeOffer;
+use std::fmt;
+use std::sync::Arc;
+use chrono::{DateTime, Duration, Utc};
+
+/// A predicate deciding whether an offer should be cancelled.
+pub type CancelFilter = Arc<dyn Fn(&TradeOffer) -> bool + Send + Sync>;
+
+/// Policy for automatically cancelling offers we sent during polls.
+///
+/// Offers are cancelled once the duration for their state has passed since they were last
+/// updated. A duration for an individual offer can be set when sending it using
+/// [`TradeOfferManager::set_cancel_duration`](crate::TradeOfferManager::set_cancel_duration), or
+/// using [`PollAction::SetCancelDuration`](crate::polling::PollAction::SetCancelDuration), which
+/// take precedence over the durations set here. Offers with a trade ID are in progress and are
+/// never cancelled.
+///
+/// # Examples
+/// ```
+/// use steam_tradeoffer_manager::polling::{CancelPolicy, PollOptions};
+/// use chrono::Duration;
+///
+/// let cancel_policy = CancelPolicy {
+///     active_duration: Some(Duration::try_minutes(30).unwrap()),
+///     needs_confirmation_duration: Some(Duration::try_minutes(5).unwrap()),
+///     cancel_confirmations: true,
+///     ..Default::default()
+/// }
+///     // Only cancel offers where we are giving items.
+///     .filter(|offer| !offer.items_to_give.is_empty());
+/// let options = PollOptions {
+///     cancel_policy,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Default)]
+pub struct CancelPolicy {
+    /// The duration after an active offer ([`TradeOfferState::Active`]) was last updated to
+    /// cancel it. Active offers will not be cancelled if this is not set.
+    pub active_duration: Option<Duration>,
+    /// The duration after an offer awaiting confirmation
+    /// ([`TradeOfferState::CreatedNeedsConfirmation`]) was last updated to cancel it. Offers
+    /// awaiting confirmation will not be cancelled if this is not set.
+    pub needs_confirmation_duration: Option<Duration>,
+    /// Only offers this returns `true` for are cancelled. All offers past their duration are
+    /// cancelled if this is not set. Set this using [`CancelPolicy::filter`].
+    pub filter: Option<CancelFilter>,
+    /// Whether to also cancel the pending mobile confirmation for offers awaiting confirmation.
+    /// Requires an identity secret.
+    pub cancel_confirmations: bool,
+}
+
+impl fmt::Debug for CancelPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancelPolicy")
+            .field("active_duration", &self.active_duration)
+            .field("needs_confirmation_duration", &self.needs_confirmation_duration)
+            .field("filter", &self.filter.as_ref().map(|_| "Fn(&TradeOffer) -> bool"))
+            .field("cancel_confirmations", &self.cancel_confirmations)
+            .finish()
+    }
+}
+
+impl CancelPolicy {
+    /// Uses the same duration for both active offers and offers awaiting confirmation.
+    pub fn with_duration(duration: Duration) -> Self {
+        Self {
+            active_duration: Some(duration),
+            needs_confirmation_duration: Some(duration),
+            ..Default::default()
+        }
+    }
+    
+    /// Sets the predicate deciding whether an offer should be cancelled.
+    pub fn filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&TradeOffer) -> bool + Send + Sync + 'static,
+    {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+    
+    /// Whether any offers can be cancelled by this policy without per-offer durations.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.active_duration.is_some() || self.needs_confirmation_duration.is_some()
+    }
+    
+    /// Checks whether the offer is past its cancel duration. `override_duration` is the duration
+    /// set for this offer specifically.
+    pub(crate) fn is_past_duration(
+        &self,
+        offer: &RawTradeOffer,
+        override_duration: Option<Duration>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        if !offer.is_our_offer || offer.tradeid.is_some() {
+            return false;
+        }
+        
+        let duration = match offer.trade_offer_state {
+            TradeOfferState::Active => override_duration.or(self.active_duration),
+            TradeOfferState::CreatedNeedsConfirmation => {
+                override_duration.or(self.needs_confirmation_duration)
+            },
+            _ => None,
+        };
+        
+        if let Some(duration) = duration {
+            offer.time_updated < now - duration
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::raw_offer;
+    
+    fn offer(state: TradeOfferState, minutes_ago: i64) -> RawTradeOffer {
+        RawTradeOffer {
+            time_updated: Utc::now() - Duration::try_minutes(minutes_ago).unwrap(),
+            ..raw_offer(1, state)
+        }
+    }
+    
+    #[test]
+    fn uses_duration_for_state() {
+        let policy = CancelPolicy {
+            active_duration: Some(Duration::try_minutes(30).unwrap()),
+            needs_confirmation_duration: Some(Duration::try_minutes(5).unwrap()),
+            ..Default::default()
+        };
+        let now = Utc::now();
+        
+        assert!(!policy.is_past_duration(&offer(TradeOfferState::Active, 10), None, now));
+        assert!(policy.is_past_duration(&offer(TradeOfferState::Active, 40), None, now));
+        assert!(policy.is_past_duration(
+            &offer(TradeOfferState::CreatedNeedsConfirmation, 10),
+            None,
+            now,
+        ));
+        assert!(!policy.is_past_duration(&offer(TradeOfferState::Accepted, 40), None, now));
+    }
+    
+    #[test]
+    fn override_takes_precedence() {
+        let policy = CancelPolicy::default();
+        let now = Utc::now();
+        let offer = offer(TradeOfferState::Active, 10);
+        
+        assert!(!policy.is_past_duration(&offer, None, now));
+        assert!(policy.is_past_duration(&offer, Some(Duration::try_minutes(5).unwrap()), now));
+    }
+}
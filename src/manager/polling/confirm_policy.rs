@@ -0,0 +1,49 @@
+use crate::response::TradeOffer;
+use std::fmt;
+use std::sync::Arc;
+
+/// A predicate deciding whether an offer should be confirmed.
+pub type ConfirmFilter = Arc<dyn Fn(&TradeOffer) -> bool + Send + Sync>;
+
+/// Policy for automatically confirming offers we sent during polls. Requires an identity secret.
+///
+/// Offers we sent that are awaiting confirmation are matched to pending mobile confirmations by
+/// their trade offer ID. Offers whose confirmation has not yet appeared are checked again on the
+/// next poll.
+///
+/// # Examples
+/// ```
+/// use steam_tradeoffer_manager::polling::{ConfirmPolicy, PollOptions};
+///
+/// let options = PollOptions {
+///     // Only confirm offers where we are receiving items.
+///     auto_confirm: Some(ConfirmPolicy::default()
+///         .filter(|offer| !offer.items_to_receive.is_empty())),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Default)]
+pub struct ConfirmPolicy {
+    /// Only offers this returns `true` for are confirmed. All offers awaiting confirmation are
+    /// confirmed if this is not set. Set this using [`ConfirmPolicy::filter`].
+    pub filter: Option<ConfirmFilter>,
+}
+
+impl fmt::Debug for ConfirmPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfirmPolicy")
+            .field("filter", &self.filter.as_ref().map(|_| "Fn(&TradeOffer) -> bool"))
+            .finish()
+    }
+}
+
+impl ConfirmPolicy {
+    /// Sets the predicate deciding whether an offer should be confirmed.
+    pub fn filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&TradeOffer) -> bool + Send + Sync + 'static,
+    {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+}
@@ -0,0 +1,133 @@
+use crate::error::Error;
+use crate::manager::session_renewer::SessionRenewer;
+use crate::mobile_api::ConfirmationApi;
+use crate::response::Confirmation;
+use std::collections::HashMap;
+use std::sync::Arc;
+use chrono::Duration;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+const DEFAULT_POLL_INTERVAL_SECONDS: i64 = 30;
+
+/// The receiver for confirmation events.
+pub type ConfirmationReceiver = mpsc::Receiver<ConfirmationEvent>;
+
+/// An event emitted by the confirmation poller.
+#[derive(Debug)]
+pub enum ConfirmationEvent {
+    /// A pending confirmation was encountered for the first time. All pending confirmations are
+    /// emitted as new on the first poll.
+    New(Confirmation),
+    /// A confirmation is no longer pending. It was accepted or cancelled, either by us or
+    /// elsewhere, or its offer or listing is no longer valid.
+    Vanished(Confirmation),
+    /// Confirmations could not be fetched. The confirmations are fetched again on the next poll.
+    Failed(Error),
+}
+
+/// Options for polling confirmations.
+#[derive(Debug, Clone, Copy)]
+pub struct ConfirmationPollOptions {
+    /// Interval to poll at. Default is 30 seconds.
+    pub poll_interval: Duration,
+}
+
+impl Default for ConfirmationPollOptions {
+    fn default() -> Self {
+        Self {
+            // unwrap is safe because the value is in range
+            poll_interval: Duration::try_seconds(DEFAULT_POLL_INTERVAL_SECONDS).unwrap(),
+        }
+    }
+}
+
+/// Spawns the task that polls confirmations. The task ends when cancelled or the receiver is
+/// dropped.
+pub(crate) fn spawn_confirmation_poller(
+    confirmation_api: Arc<dyn ConfirmationApi>,
+    session_renewer: Arc<SessionRenewer>,
+    options: ConfirmationPollOptions,
+    cancellation_token: CancellationToken,
+) -> ConfirmationReceiver {
+    let (sender, receiver) = mpsc::channel::<ConfirmationEvent>(10);
+    let poll_interval = options.poll_interval.to_std()
+        .unwrap_or(std::time::Duration::from_secs(DEFAULT_POLL_INTERVAL_SECONDS as u64))
+        // Prevents spamming requests.
+        .max(std::time::Duration::from_secs(1));
+    
+    tokio::spawn(async move {
+        // The pending confirmations from the last poll.
+        let mut pending = HashMap::new();
+        
+        loop {
+            let result = session_renewer
+                .with_renewal(|| confirmation_api.get_trade_confirmations())
+                .await;
+            let events = match result {
+                Ok(confirmations) => diff_confirmations(&mut pending, confirmations),
+                Err(error) => vec![ConfirmationEvent::Failed(error)],
+            };
+            
+            for event in events {
+                if sender.send(event).await.is_err() {
+                    // The receiver stopped listening for events.
+                    return;
+                }
+            }
+            
+            tokio::select! {
+                // Ends the task.
+                _ = cancellation_token.cancelled() => return,
+                // Waits until the next poll interval before continuing.
+                _ = async_std::task::sleep(poll_interval) => continue,
+            }
+        }
+    });
+    
+    receiver
+}
+
+/// Compares the confirmations against the pending confirmations from the last poll. Updates
+/// `pending` to the new confirmations.
+fn diff_confirmations(
+    pending: &mut HashMap<u64, Confirmation>,
+    confirmations: Vec<Confirmation>,
+) -> Vec<ConfirmationEvent> {
+    let mut previous = std::mem::take(pending);
+    let mut events = Vec::new();
+    
+    for confirmation in confirmations {
+        if previous.remove(&confirmation.id).is_none() {
+            events.push(ConfirmationEvent::New(confirmation.clone()));
+        }
+        
+        pending.insert(confirmation.id, confirmation);
+    }
+    
+    // Anything left over is no longer pending.
+    events.extend(previous.into_values().map(ConfirmationEvent::Vanished));
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::confirmation;
+    
+    #[test]
+    fn detects_new_and_vanished_confirmations() {
+        let mut pending = HashMap::new();
+        let events = diff_confirmations(&mut pending, vec![confirmation(1, 1), confirmation(2, 2)]);
+        
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|event| matches!(event, ConfirmationEvent::New(_))));
+        
+        let events = diff_confirmations(&mut pending, vec![confirmation(2, 2), confirmation(3, 3)]);
+        
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], ConfirmationEvent::New(new) if new.id == 3));
+        assert!(matches!(&events[1], ConfirmationEvent::Vanished(vanished) if vanished.id == 1));
+        assert_eq!(pending.len(), 2);
+    }
+}
@@ -1,71 +1,155 @@
 //! Models related to polling offers.
 
-mod file;
+mod cancel_policy;
+mod confirm_policy;
+mod confirmation_poller;
+mod offer_handler;
 mod poll_type;
 mod poll_action;
 mod poller;
 mod poll_data;
+mod poll_event;
+mod poll_interval;
+mod poll_status;
+mod receipt_options;
+mod store;
 
+pub use cancel_policy::{CancelPolicy, CancelFilter};
+pub use confirm_policy::{ConfirmPolicy, ConfirmFilter};
+pub use confirmation_poller::{ConfirmationEvent, ConfirmationPollOptions, ConfirmationReceiver};
+pub(crate) use confirmation_poller::spawn_confirmation_poller;
+pub use offer_handler::{OfferHandler, HandlerOptions};
+pub(crate) use offer_handler::dispatch_polls;
 pub use poll_action::PollAction;
 pub use poll_data::PollData;
+pub use poll_event::{PollEvent, Receipt};
+pub use poll_status::PollStatus;
 pub use poll_type::PollType;
+pub use receipt_options::ReceiptOptions;
 pub use poller::{Poll, Result};
+pub use store::{PollDataStore, FilePollDataStore, MemoryPollDataStore};
+#[cfg(feature = "sqlite")]
+pub use store::SqlitePollDataStore;
 /// The receiver for polling events.
 pub type PollReceiver = mpsc::Receiver<Result>;
 /// The sender for polling events.
 pub type PollSender = mpsc::Sender<PollAction>;
+/// A subscription to polls. Any number of subscribers can receive polls. Receiving returns
+/// [`RecvError::Lagged`](tokio::sync::broadcast::error::RecvError::Lagged) with the number of
+/// skipped polls if the subscriber falls behind.
+pub type PollSubscriber = broadcast::Receiver<Arc<Result>>;
 
 use poller::Poller;
+use poll_interval::PollInterval;
+use receipt_options::get_receipt_with_retries;
 
-use crate::api::SteamTradeOfferAPI;
+use crate::api::TradeOfferApi;
+use crate::manager::session_renewer::SessionRenewer;
+use crate::error::Error;
+use crate::mobile_api::ConfirmationApi;
 use crate::SteamID;
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 
 use chrono::{DateTime, Duration};
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::task::JoinSet;
 use tokio_util::sync::CancellationToken;
 
 const DEFAULT_POLL_INTERVAL_SECONDS: i64 = 30;
 const DEFAULT_FULL_UPDATE_SECONDS: i64 = 5 * 60;
+const DEFAULT_MAX_BACKOFF_SECONDS: i64 = 10 * 60;
+const DEFAULT_RATE_LIMIT_SECONDS: i64 = 2 * 60;
+const DEFAULT_BACKOFF_JITTER: f64 = 0.1;
 // Duration in milliseconds for when a poll was called too recently.
 const CALLED_TOO_RECENTLY_MILLISECONDS: i64 = 400;
 
 /// Options for polling.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct PollOptions {
     /// The duration after a sent offer has been active to cancel during a poll. Offers will
     /// not be cancelled if this is not set.
+    /// 
+    /// This is used for the durations in `cancel_policy` that are not set.
+    #[deprecated(note = "use `cancel_policy` instead, which has a duration for each state")]
     pub cancel_duration: Option<Duration>,
+    /// The policy for cancelling offers we sent during a poll. Offers will not be cancelled by
+    /// default.
+    pub cancel_policy: CancelPolicy,
+    /// The policy for confirming offers we sent that are awaiting mobile confirmation during a
+    /// poll. Requires an identity secret. Offers will not be confirmed by default.
+    pub auto_confirm: Option<ConfirmPolicy>,
     /// The duration after the last poll becomes stale and a new one must be obtained when
     /// polling using [`steam_tradeoffer_manager::polling::PollType::Auto`]. Default is 5 minutes.
     pub poll_full_update_duration: Duration,
     /// Interval to poll at. Default is 30 seconds.
     pub poll_interval: Duration,
+    /// Interval to poll at while we have sent offers awaiting a response. This is only used when
+    /// it is shorter than `poll_interval`. Default is [`None`].
+    pub active_offers_poll_interval: Option<Duration>,
+    /// When polls fail because Steam is unavailable, the interval is doubled for each consecutive
+    /// failure up to this duration. The interval returns to `poll_interval` after a successful
+    /// poll. Default is 10 minutes.
+    pub max_backoff_interval: Duration,
+    /// The minimum duration to wait after being rate limited. Default is 2 minutes.
+    pub rate_limit_interval: Duration,
+    /// The fraction of the interval to randomly add or subtract when backing off, between `0.0`
+    /// and `1.0`. Default is `0.1`.
+    pub backoff_jitter: f64,
+    /// Fetches receipts for offers that become accepted with items to receive. Each receipt is
+    /// sent as a [`PollEvent::OfferReceipt`] in a poll of its own once it is fetched, following
+    /// the [`PollEvent::OfferAccepted`] for the offer. Receipts are not fetched by default.
+    pub fetch_receipts: Option<ReceiptOptions>,
 }
 
 impl Default for PollOptions {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             cancel_duration: None,
+            cancel_policy: CancelPolicy::default(),
+            auto_confirm: None,
             // unwrap is safe because the value is in range
             poll_full_update_duration: Duration::try_seconds(DEFAULT_FULL_UPDATE_SECONDS).unwrap(),
             // unwrap is safe because the value is in range
             poll_interval: Duration::try_seconds(DEFAULT_POLL_INTERVAL_SECONDS).unwrap(),
+            active_offers_poll_interval: None,
+            // unwrap is safe because the value is in range
+            max_backoff_interval: Duration::try_seconds(DEFAULT_MAX_BACKOFF_SECONDS).unwrap(),
+            // unwrap is safe because the value is in range
+            rate_limit_interval: Duration::try_seconds(DEFAULT_RATE_LIMIT_SECONDS).unwrap(),
+            backoff_jitter: DEFAULT_BACKOFF_JITTER,
+            fetch_receipts: None,
         }
     }
 }
 
 impl PollOptions {
-    /// Uses the default values but includes a cancel duration.
+    /// Uses the default values but includes a cancel duration for both active offers and
+    /// offers awaiting confirmation.
     pub fn default_with_cancel_duration(duration: Duration) -> Self {
         Self {
-            cancel_duration: Some(duration),
+            cancel_policy: CancelPolicy::with_duration(duration),
             ..Default::default()
         }
     }
     
+    /// Gets the cancel policy, using the deprecated `cancel_duration` for durations that are not
+    /// set.
+    #[allow(deprecated)]
+    fn cancel_policy(&self) -> CancelPolicy {
+        let mut cancel_policy = self.cancel_policy.clone();
+        
+        if let Some(duration) = self.cancel_duration {
+            cancel_policy.active_duration.get_or_insert(duration);
+            cancel_policy.needs_confirmation_duration.get_or_insert(duration);
+        }
+        
+        cancel_policy
+    }
+    
     /// Checks that the durations aren't too low so API calls are not spammed.
     fn sanity_check(&mut self) {
         let one_second = Duration::try_seconds(1).unwrap();
@@ -79,6 +163,27 @@ impl PollOptions {
             log::warn!("poll_interval is less than 1 second, setting to 1 second");
             self.poll_interval = one_second;
         }
+        
+        if let Some(active_offers_poll_interval) = self.active_offers_poll_interval {
+            if active_offers_poll_interval < one_second {
+                log::warn!(
+                    "active_offers_poll_interval is less than 1 second, setting to 1 second"
+                );
+                self.active_offers_poll_interval = Some(one_second);
+            }
+        }
+        
+        if self.max_backoff_interval < self.poll_interval {
+            log::warn!("max_backoff_interval is less than poll_interval, setting to poll_interval");
+            self.max_backoff_interval = self.poll_interval;
+        }
+        
+        if !(0.0..=1.0).contains(&self.backoff_jitter) {
+            log::warn!(
+                "backoff_jitter is not between 0.0 and 1.0, setting to {DEFAULT_BACKOFF_JITTER}"
+            );
+            self.backoff_jitter = DEFAULT_BACKOFF_JITTER;
+        }
     }
 }
 
@@ -87,13 +192,20 @@ pub struct Polling {
     pub sender: mpsc::Sender<PollAction>,
     pub receiver: mpsc::Receiver<Result>,
     pub cancellation_token: CancellationToken,
+    /// The status of the polling task, updated as it changes.
+    pub status: watch::Receiver<PollStatus>,
 }
 
 impl Polling {
-    /// Creates a new polling handle.
+    /// Creates a new polling handle. `confirmation_api` is [`None`] if offers cannot be
+    /// confirmed because there is no identity secret.
     pub fn new(
         steamid: SteamID,
-        api: SteamTradeOfferAPI,
+        api: Arc<dyn TradeOfferApi>,
+        confirmation_api: Option<Arc<dyn ConfirmationApi>>,
+        store: Arc<dyn PollDataStore>,
+        session_renewer: Arc<SessionRenewer>,
+        subscribers: broadcast::Sender<Arc<Result>>,
         mut options: PollOptions,
     ) -> Self {
         // Sanity check the options.
@@ -101,10 +213,6 @@ impl Polling {
         
         let cancellation_token = CancellationToken::new();
         let token = cancellation_token.clone();
-        let poll_data = file::load_poll_data(
-            steamid,
-            &api.data_directory,
-        ).unwrap_or_default();
         // Allows sending a message into the polling handle.
         let (
             sender,
@@ -115,73 +223,316 @@ impl Polling {
             polling_sender,
             polling_receiver,
         ) = mpsc::channel::<Result>(10);
+        let (
+            status_sender,
+            status_receiver,
+        ) = watch::channel(PollStatus::default());
         
         // This is the task that performs the polling.
         tokio::spawn(async move {
-            // The asynchronous mutex allows only one poll to be performed at a time. This not only
-            // ensures that the poller is not spammed with requests but also that the state is not
-            // modified by multiple tasks at the same time.
-            let poller = Arc::new(Mutex::new(Poller {
+            let poll_data = match store.load(steamid).await {
+                Ok(poll_data) => poll_data.unwrap_or_default(),
+                Err(error) => {
+                    log::warn!("Failed to load poll data for {}: {error}", u64::from(steamid));
+                    PollData::default()
+                },
+            };
+            let poller = Poller {
                 api,
+                confirmation_api,
+                store,
                 steamid,
                 poll_data,
-                cancel_duration: options.cancel_duration,
+                cancel_policy: options.cancel_policy(),
+                confirm_policy: options.auto_confirm.clone(),
                 poll_full_update_duration: options.poll_full_update_duration,
-            }));
-            let poll_interval = options.poll_interval.to_std()
-                .unwrap_or(std::time::Duration::from_secs(DEFAULT_POLL_INTERVAL_SECONDS as u64));
-            
-            // Task that listens for poll action events.
-            tokio::spawn(receive_poll_action_events(
-                receiver,
-                polling_sender.clone(),
-                poller.clone(),
-                token.clone(),
-            ));
+                has_active_sent_offers: false,
+            };
+            let task = PollingTask {
+                poller,
+                session_renewer,
+                poll_interval: PollInterval::new(&options),
+                receipt_options: options.fetch_receipts,
+                receipts: JoinSet::new(),
+                sender: polling_sender,
+                subscribers,
+                status: status_sender,
+                next_poll: Instant::now(),
+                poll_events: HashMap::new(),
+            };
             
-            // Performs polls.
-            loop {
-                let poll = poller
-                    .lock().await
-                    .do_poll(PollType::Auto)
-                    .await;
-                
-                if let Err(_error) = polling_sender.send(poll).await {
-                    // The connection was closed or receiver stopped listening for events.
-                    break;
-                }
-                
-                tokio::select! {
-                    // Breaks out of the loop and ends the task.
-                    _ = token.cancelled() => break,
-                    // Waits until the next poll interval before continuing.
-                    _ = async_std::task::sleep(poll_interval) => continue,
-                }
-            }
+            task.run(receiver, token).await;
         });
         
         Self {
             sender,
             receiver: polling_receiver,
             cancellation_token,
+            status: status_receiver,
         }
     }
 }
 
-/// Receives poll action events.
-async fn receive_poll_action_events(
-    mut receiver: mpsc::Receiver<PollAction>,
+/// The state of the polling task. Polls and actions are handled within a single task so that
+/// only one poll is performed at a time.
+struct PollingTask {
+    poller: Poller,
+    /// Renews the session when a poll fails because we are not logged in.
+    session_renewer: Arc<SessionRenewer>,
+    poll_interval: PollInterval,
+    receipt_options: Option<ReceiptOptions>,
+    /// Tasks fetching receipts for accepted offers. Each returns a [`PollEvent::OfferReceipt`].
+    receipts: JoinSet<PollEvent>,
     sender: mpsc::Sender<Result>,
-    poller: Arc<Mutex<Poller>>,
-    cancellation_token: CancellationToken,
-) {
-    /// Checks if a poll was called too recently. Mutates the `poll_events` map to update the last
-    /// poll date to now.
+    subscribers: broadcast::Sender<Arc<Result>>,
+    status: watch::Sender<PollStatus>,
+    /// When the next automatic poll is due.
+    next_poll: Instant,
+    /// The last time each type of poll was requested. Used to prevent spam.
+    poll_events: HashMap<PollType, DateTime<chrono::Utc>>,
+}
+
+impl PollingTask {
+    /// Performs polls and handles actions until cancelled or the receiver for polls is dropped.
+    async fn run(
+        mut self,
+        mut receiver: mpsc::Receiver<PollAction>,
+        cancellation_token: CancellationToken,
+    ) {
+        // Polling continues if the sender for actions is dropped.
+        let mut receiving_actions = true;
+        
+        loop {
+            let wait = self.next_poll.saturating_duration_since(Instant::now());
+            
+            tokio::select! {
+                // Breaks out of the loop and ends the task.
+                _ = cancellation_token.cancelled() => break,
+                // Waits until the next poll is due.
+                _ = async_std::task::sleep(wait), if !self.status.borrow().paused => {
+                    let poll = self.poll(PollType::Auto).await;
+                    // Backs off when Steam is unavailable.
+                    let interval = self.poll_interval.next_interval(
+                        &poll,
+                        self.poller.has_active_sent_offers,
+                    );
+                    
+                    self.next_poll = Instant::now() + interval;
+                    
+                    if !self.send(poll).await {
+                        // The connection was closed or receiver stopped listening for events.
+                        break;
+                    }
+                },
+                // Sends receipts once they are fetched.
+                Some(event) = self.receipts.join_next(), if !self.receipts.is_empty() => {
+                    let event = match event {
+                        Ok(event) => event,
+                        Err(error) => {
+                            // The event for the accepted offer was already sent.
+                            log::warn!("Failed to fetch receipt: {error}");
+                            continue;
+                        },
+                    };
+                    
+                    if !self.send(Ok(vec![event])).await {
+                        break;
+                    }
+                },
+                message = receiver.recv(), if receiving_actions => {
+                    let Some(message) = message else {
+                        // The sender was dropped.
+                        receiving_actions = false;
+                        continue;
+                    };
+                    
+                    if !self.handle_action(message).await {
+                        break;
+                    }
+                },
+            }
+        }
+    }
+    
+    /// Performs a poll and updates the status.
+    async fn poll(
+        &mut self,
+        poll_type: PollType,
+    ) -> Result {
+        self.check_session_expiry();
+        
+        let mut poll = self.poller.do_poll(poll_type).await;
+        
+        // Renews the session and retries once if we were logged out.
+        if matches!(poll, Err(Error::NotLoggedIn)) && self.session_renewer.can_renew() {
+            match self.session_renewer.renew(false).await {
+                Ok(()) => poll = self.poller.do_poll(poll_type).await,
+                Err(error) => log::warn!("Failed to renew session after poll failed: {error}"),
+            }
+        }
+        
+        if let Ok(events) = &poll {
+            self.spawn_receipts(events);
+        }
+        
+        let state_map_size = self.poller.poll_data.state_map.len();
+        
+        self.status.send_modify(|status| {
+            status.last_poll = Some(chrono::Utc::now());
+            status.state_map_size = state_map_size;
+            
+            if let Err(error) = &poll {
+                status.last_error = Some(error.to_string());
+                status.consecutive_failures = status.consecutive_failures.saturating_add(1);
+            } else {
+                status.last_error = None;
+                status.consecutive_failures = 0;
+            }
+        });
+        
+        poll
+    }
+    
+    /// Fetches receipts in the background for accepted offers with items to receive in the poll,
+    /// if receipts are fetched.
+    fn spawn_receipts(
+        &mut self,
+        poll: &Poll,
+    ) {
+        let Some(receipt_options) = self.receipt_options else {
+            return;
+        };
+        let accepted = poll
+            .iter()
+            .filter_map(|event| match event {
+                PollEvent::OfferAccepted { offer, .. } if !offer.items_to_receive.is_empty() => {
+                    Some(offer.clone())
+                },
+                _ => None,
+            });
+        
+        for offer in accepted {
+            let api = Arc::clone(&self.poller.api);
+            let session_renewer = Arc::clone(&self.session_renewer);
+            
+            self.receipts.spawn(async move {
+                let result = get_receipt_with_retries(
+                    &*api,
+                    &session_renewer,
+                    &offer,
+                    receipt_options,
+                ).await;
+                
+                if let Err(error) = &result {
+                    log::warn!("Failed to get receipt for offer {}: {error}", offer.tradeofferid);
+                }
+                
+                PollEvent::OfferReceipt {
+                    offer,
+                    receipt: result.map_err(Arc::new),
+                }
+            });
+        }
+    }
+    
+    /// Updates the session expiry in the status. Getting the session info also warns when the
+    /// access token is about to expire.
+    fn check_session_expiry(&mut self) {
+        let session_expires_at = self.session_renewer.session_info()
+            .and_then(|info| info.expires_at);
+        
+        self.status.send_if_modified(|status| {
+            let is_modified = status.session_expires_at != session_expires_at;
+            
+            status.session_expires_at = session_expires_at;
+            is_modified
+        });
+    }
+    
+    /// Sends a poll to the receiver and subscribers. The receiver gets the original poll while
+    /// it is open and subscribers get a copy (see [`copy_poll`]), otherwise subscribers get the
+    /// original. Returns `false` once the receiver is closed and there are no subscribers, which
+    /// ends polling.
+    async fn send(
+        &self,
+        poll: Result,
+    ) -> bool {
+        if self.sender.is_closed() {
+            // This only fails if there are no subscribers.
+            return self.subscribers.send(Arc::new(poll)).is_ok();
+        }
+        
+        if self.subscribers.receiver_count() > 0 {
+            // This only fails if all subscribers were dropped since checking.
+            let _ = self.subscribers.send(Arc::new(copy_poll(&poll)));
+        }
+        
+        if self.sender.send(poll).await.is_ok() {
+            return true;
+        }
+        
+        // The receiver was dropped while sending.
+        self.subscribers.receiver_count() > 0
+    }
+    
+    /// Handles an action. Returns `false` if the task should stop.
+    async fn handle_action(
+        &mut self,
+        message: PollAction,
+    ) -> bool {
+        match message {
+            PollAction::DoPoll(poll_type) => {
+                // To prevent spam.
+                // This type of poll was called too recently.
+                if self.is_called_too_recently(poll_type) {
+                    // Ignore it.
+                    return true;
+                }
+                
+                let poll = self.poll(poll_type).await;
+                
+                // Returns false if they closed the connection.
+                return self.send(poll).await;
+            },
+            PollAction::SetCancelDuration {
+                tradeofferid,
+                duration,
+            } => {
+                self.poller.set_cancel_duration(tradeofferid, duration).await;
+            },
+            PollAction::Pause => {
+                self.status.send_modify(|status| status.paused = true);
+            },
+            PollAction::Resume => {
+                self.status.send_modify(|status| status.paused = false);
+            },
+            PollAction::UpdateOptions(mut options) => {
+                options.sanity_check();
+                self.poller.cancel_policy = options.cancel_policy();
+                self.poller.confirm_policy = options.auto_confirm.clone();
+                self.poller.poll_full_update_duration = options.poll_full_update_duration;
+                self.receipt_options = options.fetch_receipts;
+                self.poll_interval.update_options(&options);
+                
+                // Polls sooner if the new interval is shorter.
+                if let Ok(poll_interval) = options.poll_interval.to_std() {
+                    self.next_poll = self.next_poll.min(Instant::now() + poll_interval);
+                }
+            },
+            // Breaks out of the loop and ends the task.
+            PollAction::StopPolling => return false,
+        }
+        
+        true
+    }
+    
+    /// Checks if a poll was called too recently. Updates the last poll date for this type of poll
+    /// to now.
     fn is_called_too_recently(
-        poll_events: &mut HashMap<PollType, DateTime<chrono::Utc>>,
+        &mut self,
         poll_type: PollType,
     ) -> bool {
-        if let Some(last_poll_date) = poll_events.get_mut(&poll_type) {
+        if let Some(last_poll_date) = self.poll_events.get_mut(&poll_type) {
             let now = chrono::Utc::now();
             let duration = now - *last_poll_date;
             
@@ -190,44 +541,151 @@ async fn receive_poll_action_events(
             // unwrap is safe because the value for CALLED_TOO_RECENTLY_MILLISECONDS is in range
             duration < Duration::try_milliseconds(CALLED_TOO_RECENTLY_MILLISECONDS).unwrap()
         } else {
-            poll_events.insert(poll_type, chrono::Utc::now());
+            self.poll_events.insert(poll_type, chrono::Utc::now());
             false
         }
     }
+}
+
+/// Copies a poll for subscribers. Errors cannot be cloned, so each error is copied as the same
+/// variant where possible. Errors from requests are copied as [`Error::StatusCode`] if they have a
+/// status code. Other errors, such as errors parsing a response, are copied as
+/// [`Error::UnexpectedResponse`] containing the message of the original error.
+fn copy_poll(
+    poll: &Result,
+) -> Result {
+    let error = match poll {
+        Ok(events) => return Ok(events.clone()),
+        Err(error) => error,
+    };
+    
+    Err(match error {
+        Error::Parameter(error) => Error::Parameter(error.clone()),
+        Error::UnexpectedResponse(message) => Error::UnexpectedResponse(message.clone()),
+        Error::Reqwest(error) if error.status().is_some() => {
+            // unwrap is safe because the status was checked
+            Error::StatusCode(error.status().unwrap())
+        },
+        Error::StatusCode(status) => Error::StatusCode(*status),
+        Error::NotLoggedIn => Error::NotLoggedIn,
+        Error::ResponseUnsuccessful => Error::ResponseUnsuccessful,
+        Error::TradeOffer(error) => Error::TradeOffer(error.clone()),
+        Error::NoConfirmationForOffer(tradeofferid) => Error::NoConfirmationForOffer(*tradeofferid),
+        Error::Auth(error) => Error::Auth(error.clone()),
+        Error::Confirmation(error) => Error::Confirmation(error.clone()),
+        Error::MalformedResponse(message) => Error::MalformedResponse(message),
+        Error::MalformedResponseWithBody(message, body) => {
+            Error::MalformedResponseWithBody(message, body.clone())
+        },
+        Error::SteamEResult(eresult, body) => Error::SteamEResult(*eresult, body.clone()),
+        Error::BogusOfferList(message) => Error::BogusOfferList(message),
+        error => Error::UnexpectedResponse(error.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::SteamTradeOfferAPI;
+    use crate::api::response::RawTradeOffer;
+    use crate::auth::AuthClient;
+    use crate::enums::TradeOfferState;
+    use crate::fixtures::{raw_asset, raw_offer, FakeApi};
+    use crate::mobile_api::MobileAPI;
+    
+    #[test]
+    #[allow(deprecated)]
+    fn uses_deprecated_cancel_duration_for_unset_durations() {
+        let duration = Duration::try_minutes(5).unwrap();
+        let options = PollOptions {
+            cancel_duration: Some(duration),
+            cancel_policy: CancelPolicy {
+                active_duration: Some(Duration::try_minutes(30).unwrap()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let cancel_policy = options.cancel_policy();
+        
+        assert_eq!(cancel_policy.active_duration, Some(Duration::try_minutes(30).unwrap()));
+        assert_eq!(cancel_policy.needs_confirmation_duration, Some(duration));
+    }
     
-    // To prevent spam.
-    let mut poll_events: HashMap<PollType, DateTime<chrono::Utc>> = HashMap::new();
+    #[test]
+    fn copies_polls_for_subscribers() {
+        let copy = copy_poll(&Err(Error::NotLoggedIn));
+        
+        assert!(matches!(copy, Err(Error::NotLoggedIn)));
+        
+        let error = serde_json::from_str::<u32>("").unwrap_err();
+        let message = error.to_string();
+        let copy = copy_poll(&Err(Error::ParseJson(error)));
+        
+        assert!(matches!(copy, Err(Error::UnexpectedResponse(copied)) if copied.contains(&message)));
+    }
     
-    loop {
-        tokio::select! {
-            // Breaks out of the loop and ends the task.
-            _ = cancellation_token.cancelled() => break,
-            message = receiver.recv() => {
-                if let Some(message) = message {
-                    match message {
-                        PollAction::DoPoll(poll_type) => {
-                            // To prevent spam.
-                            // This type of poll was called too recently.
-                            if is_called_too_recently(&mut poll_events, poll_type) {
-                                // Ignore it.
-                                continue;
-                            }
-                            
-                            let poll = poller.lock().await.do_poll(poll_type).await;
-                            
-                            if sender.send(poll).await.is_err() {
-                                // They closed the connection.
-                                break;
-                            }
-                        },
-                        // Breaks out of the loop and ends the task.
-                        PollAction::StopPolling => break,
-                    }
-                } else {
-                    // The sender was dropped
-                    break;
-                }
-            }
-        }
+    #[tokio::test]
+    async fn polls_while_fetching_receipts() {
+        let data_directory = std::env::temp_dir().join("polling_receipts_test");
+        let session_renewer = SessionRenewer::new(
+            AuthClient::builder().build(),
+            SteamTradeOfferAPI::builder().data_directory(data_directory).build(),
+            MobileAPI::builder().build(),
+            None,
+            None,
+        );
+        let offer = RawTradeOffer {
+            tradeid: Some(1),
+            items_to_give: vec![raw_asset(1)],
+            items_to_receive: vec![raw_asset(1)],
+            ..raw_offer(1, TradeOfferState::Active)
+        };
+        let api = Arc::new(FakeApi::default());
+        
+        api.offers.lock().unwrap().push(offer);
+        
+        let Polling {
+            sender,
+            mut receiver,
+            cancellation_token: _cancellation_token,
+            mut status,
+        } = Polling::new(
+            SteamID::try_from(76561198000000000).unwrap(),
+            Arc::clone(&api) as _,
+            None,
+            Arc::new(MemoryPollDataStore::new()),
+            Arc::new(session_renewer),
+            broadcast::channel(8).0,
+            PollOptions {
+                fetch_receipts: Some(ReceiptOptions {
+                    attempts: 1,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        );
+        let poll = receiver.recv().await.unwrap();
+        
+        assert!(matches!(poll.unwrap()[..], [PollEvent::NewSentOffer(_)]));
+        
+        api.offers.lock().unwrap()[0].trade_offer_state = TradeOfferState::Accepted;
+        sender.send(PollAction::DoPoll(PollType::Auto)).await.unwrap();
+        
+        // The accepted offer is sent with its poll while the receipt is fetched.
+        let poll = receiver.recv().await.unwrap();
+        
+        assert!(matches!(poll.unwrap()[..], [PollEvent::OfferAccepted { .. }]));
+        
+        // Actions are handled while the receipt is being fetched.
+        sender.send(PollAction::Pause).await.unwrap();
+        status.wait_for(|status| status.paused).await.unwrap();
+        api.receipt_ready.notify_one();
+        
+        let poll = receiver.recv().await.unwrap();
+        
+        assert!(matches!(
+            poll.unwrap()[..],
+            [PollEvent::OfferReceipt { receipt: Ok(_), .. }],
+        ));
     }
 }
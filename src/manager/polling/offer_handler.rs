@@ -0,0 +1,364 @@
+use super::{PollEvent, Receipt, Result};
+use crate::enums::TradeOfferState;
+use crate::error::{AnyhowError, Error, HandlerError};
+use crate::response::TradeOffer;
+use std::any::Any;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use async_trait::async_trait;
+use futures::FutureExt;
+use tokio::sync::{broadcast, Semaphore};
+use tokio_util::sync::CancellationToken;
+
+const DEFAULT_CONCURRENCY: usize = 1;
+
+/// Handles events from polls. Register a handler using
+/// [`TradeOfferManager::set_offer_handler`](crate::TradeOfferManager::set_offer_handler). All
+/// methods do nothing by default, so only implement the ones you need.
+///
+/// Errors returned by handler methods and panics within them are reported to
+/// [`OfferHandler::on_handler_error`].
+///
+/// # Examples
+/// ```no_run
+/// use steam_tradeoffer_manager::TradeOfferManager;
+/// use steam_tradeoffer_manager::polling::{OfferHandler, HandlerOptions, PollOptions};
+/// use steam_tradeoffer_manager::response::TradeOffer;
+/// use steam_tradeoffer_manager::error::AnyhowError;
+///
+/// struct Bot;
+///
+/// #[steam_tradeoffer_manager::async_trait]
+/// impl OfferHandler for Bot {
+///     async fn on_new_offer(&self, offer: TradeOffer) -> Result<(), AnyhowError> {
+///         println!("New offer: {offer}");
+///         Ok(())
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let manager = TradeOfferManager::builder()
+///         .api_key("00000000000000000000000000000000".to_string())
+///         .build();
+///
+///     manager.set_offer_handler(Bot, HandlerOptions::default());
+///     manager.start_polling(PollOptions::default()).unwrap();
+/// }
+/// ```
+#[async_trait]
+pub trait OfferHandler: Send + Sync + 'static {
+    /// Called when an offer is encountered for the first time. Check
+    /// [`TradeOffer::is_our_offer`] to tell whether we sent it.
+    async fn on_new_offer(
+        &self,
+        _offer: TradeOffer,
+    ) -> std::result::Result<(), AnyhowError> {
+        Ok(())
+    }
+    
+    /// Called when the state of an offer changes. `from` is the previous state of the offer.
+    async fn on_state_changed(
+        &self,
+        _offer: TradeOffer,
+        _from: TradeOfferState,
+    ) -> std::result::Result<(), AnyhowError> {
+        Ok(())
+    }
+    
+    /// Called when the receipt for an accepted offer is fetched, if
+    /// [`PollOptions::fetch_receipts`](crate::polling::PollOptions::fetch_receipts) is set. The
+    /// offer was passed to [`OfferHandler::on_state_changed`] when it was accepted.
+    async fn on_offer_receipt(
+        &self,
+        _offer: TradeOffer,
+        _receipt: Receipt,
+    ) -> std::result::Result<(), AnyhowError> {
+        Ok(())
+    }
+    
+    /// Called when the manager cancels an offer we sent according to the
+    /// [`CancelPolicy`](crate::polling::CancelPolicy). `from` is the state of the offer before it
+    /// was cancelled.
+    async fn on_auto_cancelled(
+        &self,
+        _offer: TradeOffer,
+        _from: TradeOfferState,
+    ) -> std::result::Result<(), AnyhowError> {
+        Ok(())
+    }
+    
+    /// Called when the manager fails to cancel an offer we sent.
+    async fn on_auto_cancel_failed(
+        &self,
+        _offer: TradeOffer,
+        _error: Arc<Error>,
+    ) -> std::result::Result<(), AnyhowError> {
+        Ok(())
+    }
+    
+    /// Called when the manager confirms an offer we sent according to the
+    /// [`ConfirmPolicy`](crate::polling::ConfirmPolicy).
+    async fn on_auto_confirmed(
+        &self,
+        _offer: TradeOffer,
+    ) -> std::result::Result<(), AnyhowError> {
+        Ok(())
+    }
+    
+    /// Called when the manager fails to confirm an offer we sent.
+    async fn on_auto_confirm_failed(
+        &self,
+        _offer: TradeOffer,
+        _error: Arc<Error>,
+    ) -> std::result::Result<(), AnyhowError> {
+        Ok(())
+    }
+    
+    /// Called when a poll fails. The error is a copy while the receiver returned by
+    /// [`TradeOfferManager::start_polling`](crate::TradeOfferManager::start_polling) is in use. See
+    /// [`TradeOfferManager::subscribe_polls`](crate::TradeOfferManager::subscribe_polls).
+    async fn on_poll_error(
+        &self,
+        _error: &Error,
+    ) -> std::result::Result<(), AnyhowError> {
+        Ok(())
+    }
+    
+    /// Called when a handler method returns an error or panics, or when events were missed
+    /// because the handler fell behind. Logs the error by default.
+    fn on_handler_error(
+        &self,
+        error: HandlerError,
+    ) {
+        log::error!("{error}");
+    }
+}
+
+/// Options for dispatching events to an [`OfferHandler`].
+#[derive(Debug, Clone)]
+pub struct HandlerOptions {
+    /// The maximum number of events handled at the same time. With the default of 1, events are
+    /// handled one at a time in the order they were received.
+    pub concurrency: usize,
+}
+
+impl Default for HandlerOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+}
+
+/// Dispatches polls from `subscriber` to `handler` until cancelled or the sender is dropped.
+pub(crate) async fn dispatch_polls(
+    handler: Arc<dyn OfferHandler>,
+    mut subscriber: broadcast::Receiver<Arc<Result>>,
+    options: HandlerOptions,
+    cancellation_token: CancellationToken,
+) {
+    let semaphore = Arc::new(Semaphore::new(options.concurrency.max(1)));
+    
+    loop {
+        let poll = tokio::select! {
+            // Breaks out of the loop and ends the task.
+            _ = cancellation_token.cancelled() => break,
+            poll = subscriber.recv() => poll,
+        };
+        
+        match poll {
+            Ok(poll) => {
+                if let Ok(events) = poll.as_ref() {
+                    for event in events.clone() {
+                        dispatch(&handler, &semaphore, Dispatch::Event(event)).await;
+                    }
+                } else {
+                    dispatch(&handler, &semaphore, Dispatch::PollError(poll)).await;
+                }
+            },
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                handler.on_handler_error(HandlerError::Lagged(skipped));
+            },
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// What to dispatch to the handler.
+enum Dispatch {
+    /// An event from a poll.
+    Event(PollEvent),
+    /// A failed poll.
+    PollError(Arc<Result>),
+}
+
+/// Calls the handler in a new task once a permit is available.
+async fn dispatch(
+    handler: &Arc<dyn OfferHandler>,
+    semaphore: &Arc<Semaphore>,
+    dispatch: Dispatch,
+) {
+    // The semaphore is never closed.
+    let Ok(permit) = Arc::clone(semaphore).acquire_owned().await else {
+        return;
+    };
+    let handler = Arc::clone(handler);
+    
+    tokio::spawn(async move {
+        let (method, future) = call(&*handler, &dispatch);
+        let result = AssertUnwindSafe(future).catch_unwind().await;
+        
+        drop(permit);
+        
+        match result {
+            Ok(Ok(())) => {},
+            Ok(Err(error)) => handler.on_handler_error(HandlerError::Failed {
+                method,
+                error,
+            }),
+            Err(panic) => handler.on_handler_error(HandlerError::Panicked {
+                method,
+                message: panic_message(panic),
+            }),
+        }
+    });
+}
+
+type HandlerFuture<'a> = futures::future::BoxFuture<'a, std::result::Result<(), AnyhowError>>;
+
+/// Calls the handler method for the dispatch. Returns the name of the method and its future.
+fn call<'a>(
+    handler: &'a dyn OfferHandler,
+    dispatch: &'a Dispatch,
+) -> (&'static str, HandlerFuture<'a>) {
+    let event = match dispatch {
+        Dispatch::Event(event) => event,
+        Dispatch::PollError(poll) => {
+            let Err(error) = poll.as_ref() else {
+                return ("on_poll_error", Box::pin(async { Ok(()) }));
+            };
+            
+            return ("on_poll_error", handler.on_poll_error(error));
+        },
+    };
+    
+    match event.clone() {
+        PollEvent::NewReceivedOffer(offer) |
+        PollEvent::NewSentOffer(offer) => ("on_new_offer", handler.on_new_offer(offer)),
+        PollEvent::StateChanged { offer, from, .. } |
+        PollEvent::OfferAccepted { offer, from } |
+        PollEvent::OfferExpired { offer, from } |
+        PollEvent::EscrowStarted { offer, from } => {
+            ("on_state_changed", handler.on_state_changed(offer, from))
+        },
+        PollEvent::AutoCancelled { offer, from } => {
+            ("on_auto_cancelled", handler.on_auto_cancelled(offer, from))
+        },
+        PollEvent::AutoCancelFailed { offer, error } => {
+            ("on_auto_cancel_failed", handler.on_auto_cancel_failed(offer, error))
+        },
+        PollEvent::AutoConfirmed(offer) => {
+            ("on_auto_confirmed", handler.on_auto_confirmed(offer))
+        },
+        PollEvent::AutoConfirmFailed { offer, error } => {
+            ("on_auto_confirm_failed", handler.on_auto_confirm_failed(offer, error))
+        },
+        PollEvent::OfferReceipt { offer, receipt } => {
+            ("on_offer_receipt", handler.on_offer_receipt(offer, receipt))
+        },
+    }
+}
+
+/// Gets the message from a panic payload.
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    
+    #[derive(Default)]
+    struct Recorder {
+        new_offers: Mutex<Vec<u64>>,
+        errors: Mutex<Vec<String>>,
+    }
+    
+    #[async_trait]
+    impl OfferHandler for Arc<Recorder> {
+        async fn on_new_offer(
+            &self,
+            offer: TradeOffer,
+        ) -> std::result::Result<(), AnyhowError> {
+            if offer.tradeofferid == 2 {
+                panic!("bad offer");
+            }
+            
+            self.new_offers.lock().unwrap().push(offer.tradeofferid);
+            Ok(())
+        }
+        
+        async fn on_poll_error(
+            &self,
+            _error: &Error,
+        ) -> std::result::Result<(), AnyhowError> {
+            Err(anyhow::anyhow!("could not handle error"))
+        }
+        
+        fn on_handler_error(
+            &self,
+            error: HandlerError,
+        ) {
+            self.errors.lock().unwrap().push(error.to_string());
+        }
+    }
+    
+    fn new_offer(tradeofferid: u64) -> PollEvent {
+        PollEvent::NewReceivedOffer(TradeOffer {
+            tradeofferid,
+            ..Default::default()
+        })
+    }
+    
+    #[tokio::test]
+    async fn dispatches_events_and_reports_failures() {
+        let recorder = Arc::new(Recorder::default());
+        let (sender, subscriber) = broadcast::channel(16);
+        let task = tokio::spawn(dispatch_polls(
+            Arc::new(Arc::clone(&recorder)),
+            subscriber,
+            HandlerOptions::default(),
+            CancellationToken::new(),
+        ));
+        
+        sender.send(Arc::new(Ok(vec![new_offer(1), new_offer(2), new_offer(3)]))).unwrap();
+        sender.send(Arc::new(Err(Error::NotLoggedIn))).unwrap();
+        drop(sender);
+        task.await.unwrap();
+        
+        // Wait for the spawned handlers to finish.
+        for _ in 0..100 {
+            if recorder.errors.lock().unwrap().len() == 2 {
+                break;
+            }
+            
+            tokio::task::yield_now().await;
+        }
+        
+        assert_eq!(*recorder.new_offers.lock().unwrap(), vec![1, 3]);
+        
+        let errors = recorder.errors.lock().unwrap();
+        
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("on_new_offer") && errors[0].contains("bad offer"));
+        assert!(errors[1].contains("on_poll_error"));
+    }
+}
@@ -1,4 +1,6 @@
-use super::PollType;
+use super::{PollOptions, PollType};
+use crate::types::TradeOfferId;
+use chrono::Duration;
 
 /// An action to send to the polling task.
 #[derive(Debug, Clone)]
@@ -7,4 +9,21 @@ pub enum PollAction {
     DoPoll(PollType),
     /// Stop polling.
     StopPolling,
+    /// Sets the duration to cancel a sent offer after, taking precedence over the
+    /// [`CancelPolicy`](crate::polling::CancelPolicy). This is saved with the poll data. [`None`]
+    /// removes a previously set duration. See also
+    /// [`TradeOfferManager::set_cancel_duration`](crate::TradeOfferManager::set_cancel_duration),
+    /// which also works when not polling.
+    SetCancelDuration {
+        /// The ID of the offer.
+        tradeofferid: TradeOfferId,
+        /// The duration after the offer was last updated to cancel it.
+        duration: Option<Duration>,
+    },
+    /// Pauses automatic polling. Polls can still be performed using [`PollAction::DoPoll`].
+    Pause,
+    /// Resumes automatic polling. A poll is performed immediately if one is due.
+    Resume,
+    /// Replaces the options for polling without restarting the polling task.
+    UpdateOptions(Box<PollOptions>),
 }
@@ -20,6 +20,10 @@ pub struct PollData {
     #[serde(default)]
     /// The state map for trade offers.
     pub state_map: HashMap<TradeOfferId, TradeOfferState>,
+    #[serde(default)]
+    /// Durations in seconds to cancel specific offers after. These take precedence over the
+    /// durations in the [`CancelPolicy`](crate::polling::CancelPolicy).
+    pub cancel_durations: HashMap<TradeOfferId, i64>,
     #[serde(default, skip_serializing)]
     /// Whether the data has changed. Used for reducing file writes.
     pub changed: bool,
@@ -45,10 +49,30 @@ impl PollData {
         let length = self.state_map.len();
         
         self.state_map.retain(|tradeofferid, _| tradeofferids_to_retain.contains(tradeofferid));
+        self.cancel_durations.retain(|tradeofferid, _| {
+            tradeofferids_to_retain.contains(tradeofferid)
+        });
         // If the length of the map has changed, then the state has changed.
         self.changed = self.changed || self.state_map.len() != length;
     }
     
+    /// Gets the duration to cancel the offer after, if one was set for this offer.
+    pub fn cancel_duration(&self, tradeofferid: TradeOfferId) -> Option<Duration> {
+        self.cancel_durations.get(&tradeofferid)
+            .and_then(|seconds| Duration::try_seconds(*seconds))
+    }
+    
+    /// Sets the duration to cancel the offer after. [`None`] removes the duration.
+    pub fn set_cancel_duration(&mut self, tradeofferid: TradeOfferId, duration: Option<Duration>) {
+        if let Some(duration) = duration {
+            self.cancel_durations.insert(tradeofferid, duration.num_seconds());
+        } else {
+            self.cancel_durations.remove(&tradeofferid);
+        }
+        
+        self.changed = true;
+    }
+    
     /// Updates the `offers_since` value.
     pub fn set_offers_since(&mut self, date: ServerTime) {
         if self.offers_since != Some(date) {
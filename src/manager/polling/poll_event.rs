@@ -0,0 +1,254 @@
+use crate::enums::TradeOfferState;
+use crate::error::Error;
+use crate::response::{Asset, TradeOffer};
+use std::sync::Arc;
+
+/// The receipt for an accepted offer containing the assets we received.
+pub type Receipt = Result<Vec<Asset>, Arc<Error>>;
+
+/// An event emitted by a poll describing what happened to an offer.
+#[derive(Debug, Clone)]
+pub enum PollEvent {
+    /// An offer sent to us was encountered for the first time.
+    NewReceivedOffer(TradeOffer),
+    /// An offer we sent was encountered for the first time.
+    NewSentOffer(TradeOffer),
+    /// The state of an offer changed. More specific changes are emitted as
+    /// [`PollEvent::OfferAccepted`], [`PollEvent::OfferExpired`], [`PollEvent::EscrowStarted`]
+    /// and [`PollEvent::AutoCancelled`].
+    StateChanged {
+        /// The offer.
+        offer: TradeOffer,
+        /// The previous state of the offer.
+        from: TradeOfferState,
+        /// The new state of the offer.
+        to: TradeOfferState,
+    },
+    /// An offer was accepted and the items were exchanged.
+    OfferAccepted {
+        /// The offer.
+        offer: TradeOffer,
+        /// The previous state of the offer.
+        from: TradeOfferState,
+    },
+    /// An offer expired before it was acted on.
+    OfferExpired {
+        /// The offer.
+        offer: TradeOffer,
+        /// The previous state of the offer.
+        from: TradeOfferState,
+    },
+    /// An offer was accepted and the trade was placed on hold.
+    EscrowStarted {
+        /// The offer.
+        offer: TradeOffer,
+        /// The previous state of the offer.
+        from: TradeOfferState,
+    },
+    /// An offer we sent was cancelled by the manager because it passed the cancel duration set
+    /// in [`PollOptions`](crate::polling::PollOptions).
+    AutoCancelled {
+        /// The offer.
+        offer: TradeOffer,
+        /// The state of the offer before it was cancelled.
+        from: TradeOfferState,
+    },
+    /// An offer we sent passed the cancel duration set in
+    /// [`PollOptions`](crate::polling::PollOptions) but could not be cancelled. The offer is still
+    /// in the state it was in and cancelling will be attempted again on the next poll.
+    AutoCancelFailed {
+        /// The offer.
+        offer: TradeOffer,
+        /// The error encountered when cancelling the offer, e.g.
+        /// [`TradeOfferError::InvalidState`](crate::error::TradeOfferError::InvalidState).
+        error: Arc<Error>,
+    },
+    /// An offer we sent was confirmed by the manager according to the
+    /// [`ConfirmPolicy`](crate::polling::ConfirmPolicy). The state of the offer is updated to
+    /// [`TradeOfferState::Active`]. Offers confirmed the first time they are encountered are
+    /// preceded by a [`PollEvent::NewSentOffer`] in the same poll.
+    AutoConfirmed(TradeOffer),
+    /// An offer we sent matched the [`ConfirmPolicy`](crate::polling::ConfirmPolicy) but could not
+    /// be confirmed. Confirming will be attempted again on the next poll.
+    AutoConfirmFailed {
+        /// The offer.
+        offer: TradeOffer,
+        /// The error encountered when confirming the offer.
+        error: Arc<Error>,
+    },
+    /// The receipt for an offer reported in an earlier [`PollEvent::OfferAccepted`]. Receipts are
+    /// fetched in the background when
+    /// [`PollOptions::fetch_receipts`](crate::polling::PollOptions::fetch_receipts) is set and the
+    /// offer has items to receive, and are sent in a poll of their own.
+    OfferReceipt {
+        /// The offer.
+        offer: TradeOffer,
+        /// The receipt for the offer.
+        receipt: Receipt,
+    },
+}
+
+impl PollEvent {
+    /// Creates an event for an offer. `prev_state` is the state of the offer from the previous
+    /// poll, if it was seen before. `auto_cancelled_from` is the state of the offer before it was
+    /// cancelled by the poller, if it was cancelled.
+    pub(crate) fn new(
+        offer: TradeOffer,
+        prev_state: Option<TradeOfferState>,
+        auto_cancelled_from: Option<TradeOfferState>,
+    ) -> Self {
+        if let Some(from) = auto_cancelled_from {
+            return Self::AutoCancelled { offer, from };
+        }
+        
+        let Some(from) = prev_state else {
+            return if offer.is_our_offer {
+                Self::NewSentOffer(offer)
+            } else {
+                Self::NewReceivedOffer(offer)
+            };
+        };
+        
+        match offer.trade_offer_state {
+            TradeOfferState::Accepted => Self::OfferAccepted { offer, from },
+            TradeOfferState::Expired => Self::OfferExpired { offer, from },
+            TradeOfferState::InEscrow => Self::EscrowStarted { offer, from },
+            to => Self::StateChanged { offer, from, to },
+        }
+    }
+    
+    /// The offer this event is for.
+    pub fn offer(&self) -> &TradeOffer {
+        match self {
+            Self::NewReceivedOffer(offer) |
+            Self::NewSentOffer(offer) |
+            Self::StateChanged { offer, .. } |
+            Self::OfferAccepted { offer, .. } |
+            Self::OfferExpired { offer, .. } |
+            Self::EscrowStarted { offer, .. } |
+            Self::AutoCancelled { offer, .. } |
+            Self::AutoCancelFailed { offer, .. } |
+            Self::AutoConfirmed(offer) |
+            Self::AutoConfirmFailed { offer, .. } |
+            Self::OfferReceipt { offer, .. } => offer,
+        }
+    }
+    
+    /// Consumes the event, returning the offer.
+    pub fn into_offer(self) -> TradeOffer {
+        match self {
+            Self::NewReceivedOffer(offer) |
+            Self::NewSentOffer(offer) |
+            Self::StateChanged { offer, .. } |
+            Self::OfferAccepted { offer, .. } |
+            Self::OfferExpired { offer, .. } |
+            Self::EscrowStarted { offer, .. } |
+            Self::AutoCancelled { offer, .. } |
+            Self::AutoCancelFailed { offer, .. } |
+            Self::AutoConfirmed(offer) |
+            Self::AutoConfirmFailed { offer, .. } |
+            Self::OfferReceipt { offer, .. } => offer,
+        }
+    }
+    
+    /// The state of the offer before this event. [`None`] if this is a newly encountered offer.
+    /// For [`PollEvent::AutoCancelFailed`], [`PollEvent::AutoConfirmFailed`] and
+    /// [`PollEvent::OfferReceipt`] this is the current state of the offer.
+    pub fn previous_state(&self) -> Option<TradeOfferState> {
+        match self {
+            Self::NewReceivedOffer(_) |
+            Self::NewSentOffer(_) => None,
+            // The state has not changed.
+            Self::AutoCancelFailed { offer, .. } |
+            Self::AutoConfirmFailed { offer, .. } |
+            Self::OfferReceipt { offer, .. } => Some(offer.trade_offer_state),
+            Self::AutoConfirmed(_) => Some(TradeOfferState::CreatedNeedsConfirmation),
+            Self::StateChanged { from, .. } |
+            Self::OfferAccepted { from, .. } |
+            Self::OfferExpired { from, .. } |
+            Self::EscrowStarted { from, .. } |
+            Self::AutoCancelled { from, .. } => Some(*from),
+        }
+    }
+    
+    /// Converts the event into the offer and its previous state. This is the form polls were
+    /// emitted in before [`PollEvent`] was added.
+    pub fn into_parts(self) -> (TradeOffer, Option<TradeOfferState>) {
+        let prev_state = self.previous_state();
+        
+        (self.into_offer(), prev_state)
+    }
+}
+
+impl From<PollEvent> for (TradeOffer, Option<TradeOfferState>) {
+    fn from(event: PollEvent) -> Self {
+        event.into_parts()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn offer_with_state(is_our_offer: bool, state: TradeOfferState) -> TradeOffer {
+        TradeOffer {
+            is_our_offer,
+            trade_offer_state: state,
+            ..Default::default()
+        }
+    }
+    
+    #[test]
+    fn new_offers_are_split_by_sender() {
+        let event = PollEvent::new(offer_with_state(false, TradeOfferState::Active), None, None);
+        
+        assert!(matches!(event, PollEvent::NewReceivedOffer(_)));
+        
+        let event = PollEvent::new(offer_with_state(true, TradeOfferState::Active), None, None);
+        
+        assert!(matches!(event, PollEvent::NewSentOffer(_)));
+    }
+    
+    #[test]
+    fn state_changes_are_classified() {
+        let event = PollEvent::new(
+            offer_with_state(false, TradeOfferState::Accepted),
+            Some(TradeOfferState::Active),
+            None,
+        );
+        
+        assert!(matches!(event, PollEvent::OfferAccepted { from: TradeOfferState::Active, .. }));
+        
+        let event = PollEvent::new(
+            offer_with_state(false, TradeOfferState::Declined),
+            Some(TradeOfferState::Active),
+            None,
+        );
+        
+        assert!(matches!(event, PollEvent::StateChanged {
+            from: TradeOfferState::Active,
+            to: TradeOfferState::Declined,
+            ..
+        }));
+    }
+    
+    #[test]
+    fn auto_cancelled_takes_precedence() {
+        let event = PollEvent::new(
+            offer_with_state(true, TradeOfferState::Canceled),
+            Some(TradeOfferState::Active),
+            Some(TradeOfferState::Active),
+        );
+        let (offer, prev_state) = event.into_parts();
+        
+        assert_eq!(offer.trade_offer_state, TradeOfferState::Canceled);
+        assert_eq!(prev_state, Some(TradeOfferState::Active));
+    }
+    
+    #[test]
+    fn auto_confirmed_was_awaiting_confirmation() {
+        let event = PollEvent::AutoConfirmed(offer_with_state(true, TradeOfferState::Active));
+        
+        assert_eq!(event.previous_state(), Some(TradeOfferState::CreatedNeedsConfirmation));
+    }
+}
@@ -0,0 +1,204 @@
+use super::{PollOptions, Result};
+use crate::error::{Error, TradeOfferError};
+use chrono::Duration;
+use reqwest::StatusCode;
+
+/// The kind of failure a poll encountered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Failure {
+    /// We are being rate limited.
+    RateLimited,
+    /// Steam is having issues or the request timed out.
+    Transient,
+    /// Any other error. These are not backed off from.
+    Other,
+}
+
+impl From<&Error> for Failure {
+    fn from(error: &Error) -> Self {
+        match error {
+            Error::StatusCode(StatusCode::TOO_MANY_REQUESTS) => Self::RateLimited,
+            Error::StatusCode(status) if status.is_server_error() => Self::Transient,
+            Error::Reqwest(error) if error.is_timeout() || error.is_connect() => Self::Transient,
+            Error::TradeOffer(TradeOfferError::ServiceUnavailable) |
+            Error::BogusOfferList(_) => Self::Transient,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Determines how long to wait between polls.
+#[derive(Debug)]
+pub(crate) struct PollInterval {
+    poll_interval: Duration,
+    active_offers_poll_interval: Option<Duration>,
+    max_backoff_interval: Duration,
+    rate_limit_interval: Duration,
+    backoff_jitter: f64,
+    /// The number of polls in a row that failed with an error that is backed off from.
+    consecutive_failures: u32,
+}
+
+impl PollInterval {
+    /// Creates a new [`PollInterval`] from the options.
+    pub fn new(options: &PollOptions) -> Self {
+        Self {
+            poll_interval: options.poll_interval,
+            active_offers_poll_interval: options.active_offers_poll_interval,
+            max_backoff_interval: options.max_backoff_interval,
+            rate_limit_interval: options.rate_limit_interval,
+            backoff_jitter: options.backoff_jitter,
+            consecutive_failures: 0,
+        }
+    }
+    
+    /// Updates the intervals from the options. Consecutive failures are kept.
+    pub fn update_options(&mut self, options: &PollOptions) {
+        *self = Self {
+            consecutive_failures: self.consecutive_failures,
+            ..Self::new(options)
+        };
+    }
+    
+    /// Gets the duration to wait until the next poll based on the result of the last poll.
+    pub fn next_interval(
+        &mut self,
+        result: &Result,
+        has_active_sent_offers: bool,
+    ) -> std::time::Duration {
+        let failure = result.as_ref().err().map(Failure::from);
+        let interval = self.next_interval_without_jitter(failure, has_active_sent_offers);
+        let interval = if self.consecutive_failures > 0 {
+            with_jitter(interval, self.backoff_jitter, rand::random::<f64>())
+        } else {
+            interval
+        };
+        
+        interval.to_std().unwrap_or(std::time::Duration::from_secs(1))
+    }
+    
+    fn next_interval_without_jitter(
+        &mut self,
+        failure: Option<Failure>,
+        has_active_sent_offers: bool,
+    ) -> Duration {
+        match failure {
+            None => self.consecutive_failures = 0,
+            // Other errors are not caused by Steam being unavailable so they are not backed off
+            // from.
+            Some(Failure::Other) => self.consecutive_failures = 0,
+            Some(_) => self.consecutive_failures = self.consecutive_failures.saturating_add(1),
+        }
+        
+        if self.consecutive_failures == 0 {
+            return match self.active_offers_poll_interval {
+                Some(interval) if has_active_sent_offers => interval.min(self.poll_interval),
+                _ => self.poll_interval,
+            };
+        }
+        
+        // Doubles for each consecutive failure. The exponent is capped to avoid overflow.
+        let multiplier = 2_i32.pow(self.consecutive_failures.min(16));
+        let interval = (self.poll_interval * multiplier).min(self.max_backoff_interval);
+        
+        if failure == Some(Failure::RateLimited) {
+            interval.max(self.rate_limit_interval)
+        } else {
+            interval
+        }
+    }
+}
+
+/// Randomizes the duration by up to `jitter` (a fraction of the duration) in either direction.
+/// `random` is a value between 0 and 1.
+fn with_jitter(duration: Duration, jitter: f64, random: f64) -> Duration {
+    let milliseconds = duration.num_milliseconds() as f64;
+    let offset = milliseconds * jitter * (random * 2.0 - 1.0);
+    
+    Duration::try_milliseconds((milliseconds + offset) as i64).unwrap_or(duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn seconds(seconds: i64) -> Duration {
+        Duration::try_seconds(seconds).unwrap()
+    }
+    
+    fn poll_interval() -> PollInterval {
+        PollInterval::new(&PollOptions {
+            poll_interval: seconds(30),
+            active_offers_poll_interval: Some(seconds(10)),
+            max_backoff_interval: seconds(300),
+            rate_limit_interval: seconds(120),
+            ..Default::default()
+        })
+    }
+    
+    #[test]
+    fn backs_off_and_recovers() {
+        let mut interval = poll_interval();
+        
+        assert_eq!(
+            interval.next_interval_without_jitter(Some(Failure::Transient), false),
+            seconds(60),
+        );
+        assert_eq!(
+            interval.next_interval_without_jitter(Some(Failure::Transient), false),
+            seconds(120),
+        );
+        assert_eq!(
+            interval.next_interval_without_jitter(Some(Failure::Transient), false),
+            seconds(240),
+        );
+        assert_eq!(
+            interval.next_interval_without_jitter(Some(Failure::Transient), false),
+            seconds(300),
+        );
+        assert_eq!(interval.next_interval_without_jitter(None, false), seconds(30));
+        assert_eq!(interval.consecutive_failures, 0);
+    }
+    
+    #[test]
+    fn rate_limits_wait_at_least_rate_limit_interval() {
+        let mut interval = poll_interval();
+        
+        assert_eq!(
+            interval.next_interval_without_jitter(Some(Failure::RateLimited), false),
+            seconds(120),
+        );
+        assert_eq!(interval.next_interval_without_jitter(Some(Failure::Other), false), seconds(30));
+    }
+    
+    #[test]
+    fn polls_faster_with_active_sent_offers() {
+        let mut interval = poll_interval();
+        
+        assert_eq!(interval.next_interval_without_jitter(None, true), seconds(10));
+        assert_eq!(
+            interval.next_interval_without_jitter(Some(Failure::Transient), true),
+            seconds(60),
+        );
+    }
+    
+    #[test]
+    fn jitter_stays_in_range() {
+        assert_eq!(with_jitter(seconds(100), 0.1, 0.0), seconds(90));
+        assert_eq!(with_jitter(seconds(100), 0.1, 0.5), seconds(100));
+        assert_eq!(with_jitter(seconds(100), 0.1, 1.0), seconds(110));
+    }
+    
+    #[test]
+    fn classifies_errors() {
+        assert_eq!(
+            Failure::from(&Error::StatusCode(StatusCode::TOO_MANY_REQUESTS)),
+            Failure::RateLimited,
+        );
+        assert_eq!(
+            Failure::from(&Error::TradeOffer(TradeOfferError::ServiceUnavailable)),
+            Failure::Transient,
+        );
+        assert_eq!(Failure::from(&Error::NotLoggedIn), Failure::Other);
+    }
+}
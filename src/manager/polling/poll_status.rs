@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+
+/// The status of the polling task. Get it using
+/// [`TradeOfferManager::poll_status`](crate::TradeOfferManager::poll_status).
+#[derive(Debug, Clone, Default)]
+pub struct PollStatus {
+    /// Whether automatic polling is paused.
+    pub paused: bool,
+    /// The date of the last completed poll.
+    pub last_poll: Option<DateTime<Utc>>,
+    /// The error encountered by the last poll, if it failed.
+    pub last_error: Option<String>,
+    /// The number of polls in a row that failed.
+    pub consecutive_failures: u32,
+    /// The number of offers in the poll data state map.
+    pub state_map_size: usize,
+    /// When the access token of the session expires, if known.
+    pub session_expires_at: Option<DateTime<Utc>>,
+}
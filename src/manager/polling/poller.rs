@@ -1,19 +1,29 @@
-use super::{file, PollData, PollType};
+use super::{
+    CancelPolicy,
+    ConfirmPolicy,
+    PollData,
+    PollDataStore,
+    PollEvent,
+    PollType,
+};
 use crate::api::request::GetTradeOffersOptions;
-use crate::api::SteamTradeOfferAPI;
-use crate::enums::TradeOfferState;
+use crate::api::response::RawTradeOffer;
+use crate::api::TradeOfferApi;
+use crate::enums::{ConfirmationType, TradeOfferState};
 use crate::error::Error;
+use crate::mobile_api::ConfirmationApi;
 use crate::response::TradeOffer;
 use crate::time;
-use crate::types::TradeOfferId;
+use crate::types::{ClassInfoMap, TradeOfferId};
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 use chrono::Duration;
 use steamid_ng::SteamID;
 
-/// A poll containing new offers. For each item in the vector, the first element is the
-/// [`TradeOffer`]. The second part is the previous [`TradeOfferState`] if this is not a newly
-/// encountered offer.
-pub type Poll = Vec<(TradeOffer, Option<TradeOfferState>)>;
+/// A poll containing an event for each new or changed offer. Use
+/// [`PollEvent::into_parts`] to convert an event into the offer and its previous
+/// [`TradeOfferState`].
+pub type Poll = Vec<PollEvent>;
 /// The result of a poll.
 pub type Result = std::result::Result<Poll, Error>;
 
@@ -22,10 +32,16 @@ const OFFERS_SINCE_ALL_TIMESTAMP: i64 = 1;
 
 pub struct Poller {
     pub steamid: SteamID,
-    pub api: SteamTradeOfferAPI,
-    pub cancel_duration: Option<Duration>,
+    pub api: Arc<dyn TradeOfferApi>,
+    /// [`None`] if offers cannot be confirmed because there is no identity secret.
+    pub confirmation_api: Option<Arc<dyn ConfirmationApi>>,
+    pub store: Arc<dyn PollDataStore>,
+    pub cancel_policy: CancelPolicy,
+    pub confirm_policy: Option<ConfirmPolicy>,
     pub poll_full_update_duration: Duration,
     pub poll_data: PollData,
+    /// Whether the last poll returned sent offers awaiting a response.
+    pub has_active_sent_offers: bool,
 }
 
 impl Poller {
@@ -61,7 +77,7 @@ impl Poller {
         }
         
         let (
-            mut offers,
+            offers,
             descriptions,
         ) = self.api.get_raw_trade_offers(&GetTradeOffersOptions {
             active_only,
@@ -72,6 +88,13 @@ impl Poller {
             historical_cutoff: Some(time::timestamp_to_server_time(offers_since)),
         }).await?;
         
+        if is_full_update {
+            // Nothing is updated if the response looks wrong.
+            if let Some(message) = check_full_update(&self.poll_data, &offers) {
+                return Err(Error::BogusOfferList(message));
+            }
+        }
+        
         if !poll_type.is_active_only() {
             self.poll_data.set_last_poll(now);
         }
@@ -80,35 +103,32 @@ impl Poller {
             self.poll_data.set_last_poll_full_update(now);
         }
         
-        // Vec of offers that were cancelled.
-        let cancelled_offers = if let Some(cancel_duration) = self.cancel_duration {
-            let cancel_time = chrono::Utc::now() - cancel_duration;
-            // Cancels all offers older than cancel_time.
-            let cancel_futures = offers
-                .iter_mut()
-                .filter(|offer| {
-                    let is_active_state = {
-                        offer.trade_offer_state == TradeOfferState::Active ||
-                        // We also want to cancel offers that have not yet been confirmed.
-                        offer.trade_offer_state == TradeOfferState::CreatedNeedsConfirmation
-                    };
-                    
-                    is_active_state &&
-                    offer.is_our_offer &&
-                    offer.time_updated < cancel_time &&
-                    // offers with a tradeid are in progress and cannot be cancelled.
-                    offer.tradeid.is_none()
-                })
-                .map(|offer| self.api.cancel_offer(offer.tradeofferid))
-                .collect::<Vec<_>>();
-            
-            futures::future::join_all(cancel_futures).await
-                .into_iter()
-                .filter_map(|offer| offer.ok())
-                .collect::<Vec<_>>()
-        } else {
-            Vec::new()
-        };
+        // Map of offers that were cancelled and their states before being cancelled, and map of
+        // offers that failed to be cancelled and the errors encountered.
+        let (
+            cancelled_offers,
+            mut cancel_failures,
+        ) = self.cancel_offers(&offers, descriptions.as_ref()).await;
+        // Set of offers that were confirmed, and map of offers that failed to be confirmed and the
+        // errors encountered.
+        let (
+            confirmed_offers,
+            mut confirm_failures,
+        ) = self.confirm_offers(&offers, descriptions.as_ref(), &cancelled_offers).await;
+        
+        self.has_active_sent_offers = offers
+            .iter()
+            .any(|offer| {
+                let is_active = {
+                    offer.trade_offer_state == TradeOfferState::Active ||
+                    confirmed_offers.contains(&offer.tradeofferid)
+                };
+                
+                offer.is_our_offer &&
+                is_active &&
+                !cancelled_offers.contains_key(&offer.tradeofferid)
+            });
+        
         // For reducing file writes, keep track of whether the state of poll data has changed.
         let mut prev_states_map: HashMap<TradeOfferId, TradeOfferState> = HashMap::new();
         let mut poll: Vec<_> = Vec::new();
@@ -120,8 +140,10 @@ impl Poller {
         for mut offer in offers {
             // This offer was successfully cancelled above...
             // We need to update its state here.
-            if cancelled_offers.contains(&offer.tradeofferid) {
+            if cancelled_offers.contains_key(&offer.tradeofferid) {
                 offer.trade_offer_state = TradeOfferState::Canceled;
+            } else if confirmed_offers.contains(&offer.tradeofferid) {
+                offer.trade_offer_state = TradeOfferState::Active;
             }
             
             // No need to insert into the state map if this isn't a full update.
@@ -147,6 +169,12 @@ impl Poller {
                     prev_states_map.insert(offer.tradeofferid, *poll_trade_offer_state);
                     poll.push(offer);
                 },
+                // Nothing has changed, but the offer failed to be cancelled or confirmed and must be
+                // reported.
+                Some(_) if {
+                    cancel_failures.contains_key(&offer.tradeofferid) ||
+                    confirm_failures.contains_key(&offer.tradeofferid)
+                } => poll.push(offer),
                 // Nothing has changed...
                 Some(_) => {},
                 // This is a new offer
@@ -169,41 +197,453 @@ impl Poller {
         } else {
             self.api.map_raw_trade_offers(poll).await?
         };
-        let poll = if offers.is_empty() {
-            // map_raw_trade_offers may have excluded some offers - the state of the poll data
-            // is not updated until all descriptions are loaded for the offer
-            Vec::new()
-        } else {
+        // map_raw_trade_offers may have excluded some offers - the state of the poll data is not
+        // updated until all descriptions are loaded for the offer
+        let mut poll = Vec::with_capacity(offers.len());
+        
+        if !offers.is_empty() {
             self.poll_data.changed = true;
-            offers
-                .into_iter()
-                // Combines changed state maps.
-                .map(|offer| {
-                    let prev_state = prev_states_map.remove(&offer.tradeofferid);
-                    
-                    // insert new state into map
-                    self.poll_data.state_map.insert(
-                        offer.tradeofferid,
-                        offer.trade_offer_state,
-                    );
+        }
+
+        for offer in offers {
+            let tradeofferid = offer.tradeofferid;
+            let prev_state = prev_states_map.remove(&tradeofferid);
+            let auto_cancelled_from = cancelled_offers.get(&tradeofferid).copied();
+            let cancel_error = cancel_failures.remove(&tradeofferid);
+            let confirm_error = confirm_failures.remove(&tradeofferid);
+            // Offers that failed to be cancelled or confirmed are included even if their state is
+            // unchanged.
+            let is_changed = {
+                self.poll_data.state_map.get(&tradeofferid) != Some(&offer.trade_offer_state)
+            };
+            let is_new = !self.poll_data.state_map.contains_key(&tradeofferid);
+
+            // insert new state into map
+            self.poll_data.state_map.insert(tradeofferid, offer.trade_offer_state);
+            
+            // Offers no longer awaiting a response cannot be cancelled.
+            if !matches!(
+                offer.trade_offer_state,
+                TradeOfferState::Active | TradeOfferState::CreatedNeedsConfirmation
+            ) {
+                self.poll_data.cancel_durations.remove(&tradeofferid);
+            }
+
+            if is_changed {
+                if confirmed_offers.contains(&tradeofferid) {
+                    // Offers confirmed the first time they are seen are still new offers.
+                    if is_new {
+                        poll.push(PollEvent::new(offer.clone(), None, None));
+                    }
                     
-                    (offer, prev_state)
-                })
-                .collect::<Vec<_>>()
-        };
+                    poll.push(PollEvent::AutoConfirmed(offer.clone()));
+                } else {
+                    poll.push(PollEvent::new(offer.clone(), prev_state, auto_cancelled_from));
+                }
+            }
+            
+            if let Some(error) = cancel_error {
+                poll.push(PollEvent::AutoCancelFailed {
+                    offer: offer.clone(),
+                    error: Arc::new(error),
+                });
+            }
+            
+            if let Some(error) = confirm_error {
+                poll.push(PollEvent::AutoConfirmFailed { offer, error });
+            }
+        }
         
         // Only save if changes were detected.
         if self.poll_data.changed {
             self.poll_data.changed = false;
             // This could be saved in a background task, but for simplicity, we await here.
-            // Saving the file takes a negligible amount of time (usually under a ms on an SSD).
-            let _ = file::save_poll_data(
-                self.steamid,
-                &serde_json::to_string(&self.poll_data)?,
-                &self.api.data_directory,
-            ).await;
+            // Saving to the default file store takes a negligible amount of time (usually under
+            // a ms on an SSD).
+            if let Err(error) = self.store.save(self.steamid, &self.poll_data).await {
+                log::warn!("Failed to save poll data for {}: {error}", u64::from(self.steamid));
+            }
         }
         
         Ok(poll)
     }
+    
+    /// Sets the duration to cancel an offer after and saves the poll data.
+    pub async fn set_cancel_duration(
+        &mut self,
+        tradeofferid: TradeOfferId,
+        duration: Option<Duration>,
+    ) {
+        self.poll_data.set_cancel_duration(tradeofferid, duration);
+        
+        match self.store.save(self.steamid, &self.poll_data).await {
+            Ok(()) => self.poll_data.changed = false,
+            // The poll data is saved again after the next poll that changes it.
+            Err(error) => {
+                log::warn!("Failed to save poll data for {}: {error}", u64::from(self.steamid));
+            },
+        }
+    }
+    
+    /// Cancels offers according to the cancel policy. Returns a map of offers that were cancelled
+    /// and their states before being cancelled, and a map of offers that failed to be cancelled
+    /// and the errors encountered.
+    async fn cancel_offers(
+        &self,
+        offers: &[RawTradeOffer],
+        descriptions: Option<&ClassInfoMap>,
+    ) -> (HashMap<TradeOfferId, TradeOfferState>, HashMap<TradeOfferId, Error>) {
+        let mut cancelled_offers = HashMap::new();
+        let mut cancel_failures = HashMap::new();
+        
+        if !self.cancel_policy.is_enabled() && self.poll_data.cancel_durations.is_empty() {
+            return (cancelled_offers, cancel_failures);
+        }
+        
+        let now = chrono::Utc::now();
+        let mut to_cancel = offers
+            .iter()
+            .filter(|offer| self.cancel_policy.is_past_duration(
+                offer,
+                self.poll_data.cancel_duration(offer.tradeofferid),
+                now,
+            ))
+            .map(|offer| (offer.tradeofferid, offer.trade_offer_state))
+            .collect::<HashMap<_, _>>();
+        
+        if to_cancel.is_empty() {
+            return (cancelled_offers, cancel_failures);
+        }
+        
+        if let Some(filter) = &self.cancel_policy.filter {
+            let candidates = offers
+                .iter()
+                .filter(|offer| to_cancel.contains_key(&offer.tradeofferid))
+                .cloned()
+                .collect::<Vec<_>>();
+            let allowed = self.map_filter_candidates(candidates, descriptions).await
+                .iter()
+                .filter(|offer| filter(offer))
+                .map(|offer| offer.tradeofferid)
+                .collect::<HashSet<_>>();
+            
+            // Offers with missing descriptions are also excluded and will be checked again on the
+            // next poll.
+            to_cancel.retain(|tradeofferid, _| allowed.contains(tradeofferid));
+        }
+        
+        let cancel_futures = to_cancel
+            .into_iter()
+            .map(|(tradeofferid, state)| {
+                let cancel = self.api.cancel_offer(tradeofferid);
+                
+                async move { (tradeofferid, state, cancel.await) }
+            })
+            .collect::<Vec<_>>();
+        
+        for (tradeofferid, state, result) in futures::future::join_all(cancel_futures).await {
+            match result {
+                Ok(_) => {
+                    cancelled_offers.insert(tradeofferid, state);
+                },
+                Err(error) => {
+                    log::warn!("Failed to cancel offer {tradeofferid}: {error}");
+                    cancel_failures.insert(tradeofferid, error);
+                },
+            }
+        }
+        
+        let has_unconfirmed = cancelled_offers
+            .values()
+            .any(|state| *state == TradeOfferState::CreatedNeedsConfirmation);
+        
+        if self.cancel_policy.cancel_confirmations && has_unconfirmed {
+            self.cancel_confirmations(&cancelled_offers).await;
+        }
+        
+        (cancelled_offers, cancel_failures)
+    }
+    
+    /// Confirms offers we sent that are awaiting confirmation according to the confirm policy.
+    /// Returns a set of offers that were confirmed and a map of offers that failed to be confirmed
+    /// and the errors encountered.
+    async fn confirm_offers(
+        &self,
+        offers: &[RawTradeOffer],
+        descriptions: Option<&ClassInfoMap>,
+        cancelled_offers: &HashMap<TradeOfferId, TradeOfferState>,
+    ) -> (HashSet<TradeOfferId>, HashMap<TradeOfferId, Arc<Error>>) {
+        let mut confirmed_offers = HashSet::new();
+        let mut confirm_failures = HashMap::new();
+        
+        let Some(confirm_policy) = &self.confirm_policy else {
+            return (confirmed_offers, confirm_failures);
+        };
+        
+        let mut candidates = offers
+            .iter()
+            .filter(|offer| {
+                offer.is_our_offer &&
+                offer.trade_offer_state == TradeOfferState::CreatedNeedsConfirmation &&
+                !cancelled_offers.contains_key(&offer.tradeofferid)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        
+        if candidates.is_empty() {
+            return (confirmed_offers, confirm_failures);
+        }
+        
+        let Some(confirmation_api) = &self.confirmation_api else {
+            log::warn!("Offers cannot be confirmed without an identity secret");
+            return (confirmed_offers, confirm_failures);
+        };
+        
+        if let Some(filter) = &confirm_policy.filter {
+            let allowed = self.map_filter_candidates(candidates.clone(), descriptions).await
+                .iter()
+                .filter(|offer| filter(offer))
+                .map(|offer| offer.tradeofferid)
+                .collect::<HashSet<_>>();
+            
+            // Offers with missing descriptions are also excluded and will be checked again on the
+            // next poll.
+            candidates.retain(|offer| allowed.contains(&offer.tradeofferid));
+        }
+        
+        if candidates.is_empty() {
+            return (confirmed_offers, confirm_failures);
+        }
+        
+        let confirmations = match confirmation_api.get_trade_confirmations().await {
+            Ok(confirmations) => confirmations,
+            Err(error) => {
+                log::warn!("Failed to get confirmations for offers to confirm: {error}");
+                
+                // Every offer failed to be confirmed.
+                let error = Arc::new(error);
+                
+                for offer in candidates {
+                    confirm_failures.insert(offer.tradeofferid, Arc::clone(&error));
+                }
+                
+                return (confirmed_offers, confirm_failures);
+            },
+        };
+        let confirmations = confirmations
+            .into_iter()
+            .filter(|confirmation| {
+                confirmation.r#type == ConfirmationType::Trade &&
+                candidates.iter().any(|offer| offer.tradeofferid == confirmation.creator_id)
+            })
+            .collect::<Vec<_>>();
+        let results = confirmation_api.accept_confirmations(&confirmations).await;
+        
+        // Offers without a confirmation are checked again on the next poll.
+        for (confirmation, result) in confirmations.iter().zip(results) {
+            let tradeofferid = confirmation.creator_id;
+            
+            match result {
+                Ok(_) => {
+                    confirmed_offers.insert(tradeofferid);
+                },
+                Err(error) => {
+                    log::warn!("Failed to confirm offer {tradeofferid}: {error}");
+                    confirm_failures.insert(tradeofferid, Arc::new(error));
+                },
+            }
+        }
+        
+        (confirmed_offers, confirm_failures)
+    }
+    
+    /// Maps raw offers to offers with descriptions so they can be checked against a filter.
+    /// Offers with missing descriptions are excluded.
+    async fn map_filter_candidates(
+        &self,
+        candidates: Vec<RawTradeOffer>,
+        descriptions: Option<&ClassInfoMap>,
+    ) -> Vec<TradeOffer> {
+        if let Some(descriptions) = descriptions {
+            return self.api.map_raw_trade_offers_with_descriptions(
+                candidates,
+                descriptions.clone(),
+            );
+        }
+        
+        match self.api.map_raw_trade_offers(candidates).await {
+            Ok(candidates) => candidates,
+            Err(error) => {
+                log::warn!("Failed to load descriptions for offers: {error}");
+                Vec::new()
+            },
+        }
+    }
+    
+    /// Cancels the pending mobile confirmations for cancelled offers that were awaiting
+    /// confirmation. Errors are logged.
+    async fn cancel_confirmations(
+        &self,
+        cancelled_offers: &HashMap<TradeOfferId, TradeOfferState>,
+    ) {
+        let Some(confirmation_api) = &self.confirmation_api else {
+            log::warn!("Confirmations cannot be cancelled without an identity secret");
+            return;
+        };
+        let confirmations = match confirmation_api.get_trade_confirmations().await {
+            Ok(confirmations) => confirmations,
+            Err(error) => {
+                log::warn!("Failed to get confirmations for cancelled offers: {error}");
+                return;
+            },
+        };
+        
+        for confirmation in confirmations {
+            let is_cancelled_offer = cancelled_offers
+                .get(&confirmation.creator_id)
+                .is_some_and(|state| *state == TradeOfferState::CreatedNeedsConfirmation);
+            
+            if !is_cancelled_offer {
+                continue;
+            }
+            
+            if let Err(error) = confirmation_api.cancel_confirmation(&confirmation).await {
+                log::warn!(
+                    "Failed to cancel confirmation for offer {}: {error}",
+                    confirmation.creator_id,
+                );
+            }
+        }
+    }
+}
+
+/// Checks the offers returned by a full update for signs that Steam returned a bogus response.
+/// Returns a message describing the issue if one is detected.
+fn check_full_update(
+    poll_data: &PollData,
+    offers: &[RawTradeOffer],
+) -> Option<&'static str> {
+    if offers.is_empty() {
+        let has_active_offers = poll_data.state_map
+            .values()
+            .any(|state| matches!(
+                state,
+                TradeOfferState::Active | TradeOfferState::CreatedNeedsConfirmation
+            ));
+        
+        if has_active_offers {
+            return Some("no offers were returned but there are active offers");
+        }
+    } else if offers.iter().all(|offer| offer.is_glitched()) {
+        // A single glitched offer can happen normally, but every offer missing items cannot.
+        return Some("all offers are missing items");
+    }
+    
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{raw_asset, raw_offer, FakeApi, FakeConfirmationApi};
+    use crate::manager::polling::MemoryPollDataStore;
+    use std::sync::Mutex;
+    
+    fn poller(
+        offers: Vec<RawTradeOffer>,
+        confirmation_api: Option<Arc<dyn ConfirmationApi>>,
+    ) -> Poller {
+        Poller {
+            steamid: SteamID::try_from(76561198000000000).unwrap(),
+            api: Arc::new(FakeApi {
+                offers: Mutex::new(offers),
+                ..Default::default()
+            }),
+            confirmation_api,
+            store: Arc::new(MemoryPollDataStore::new()),
+            cancel_policy: CancelPolicy::default(),
+            confirm_policy: Some(ConfirmPolicy::default()),
+            poll_full_update_duration: Duration::try_minutes(5).unwrap(),
+            poll_data: PollData::default(),
+            has_active_sent_offers: false,
+        }
+    }
+    
+    /// Creates an offer we sent, optionally with an item we give.
+    fn sent_offer(tradeofferid: TradeOfferId, has_items: bool) -> RawTradeOffer {
+        let items_to_give = if has_items {
+            vec![raw_asset(1)]
+        } else {
+            Vec::new()
+        };
+        
+        RawTradeOffer {
+            items_to_give,
+            ..raw_offer(tradeofferid, TradeOfferState::Active)
+        }
+    }
+    
+    #[test]
+    fn detects_empty_list_with_active_offers() {
+        let mut poll_data = PollData::default();
+        
+        assert!(check_full_update(&poll_data, &[]).is_none());
+        
+        poll_data.state_map.insert(1, TradeOfferState::Accepted);
+        
+        assert!(check_full_update(&poll_data, &[]).is_none());
+        
+        poll_data.state_map.insert(2, TradeOfferState::Active);
+        
+        assert!(check_full_update(&poll_data, &[]).is_some());
+    }
+    
+    #[test]
+    fn detects_offers_missing_items() {
+        let poll_data = PollData::default();
+        
+        let some_glitched = [sent_offer(1, false), sent_offer(2, true)];
+        let all_glitched = [sent_offer(1, false), sent_offer(2, false)];
+        
+        assert!(check_full_update(&poll_data, &some_glitched).is_none());
+        assert!(check_full_update(&poll_data, &all_glitched).is_some());
+    }
+    
+    #[tokio::test]
+    async fn removes_cancel_durations_for_finished_offers() {
+        let mut offers = vec![sent_offer(1, true), sent_offer(2, true)];
+        
+        offers[1].trade_offer_state = TradeOfferState::Accepted;
+        
+        let mut poller = poller(offers, None);
+        let duration = Some(Duration::try_days(1).unwrap());
+        
+        poller.poll_data.set_cancel_duration(1, duration);
+        poller.poll_data.set_cancel_duration(2, duration);
+        poller.do_poll(PollType::Auto).await.unwrap();
+        
+        assert!(poller.poll_data.cancel_duration(1).is_some());
+        assert!(poller.poll_data.cancel_duration(2).is_none());
+    }
+    
+    #[tokio::test]
+    async fn new_offers_confirmed_in_the_same_poll_are_new_offers() {
+        let mut offer = sent_offer(1, true);
+        
+        offer.trade_offer_state = TradeOfferState::CreatedNeedsConfirmation;
+        
+        let confirmation_api = Arc::new(FakeConfirmationApi {
+            tradeofferids: vec![1],
+            ..Default::default()
+        });
+        let mut poller = poller(vec![offer], Some(Arc::clone(&confirmation_api) as _));
+        let poll = poller.do_poll(PollType::Auto).await.unwrap();
+        
+        assert_eq!(*confirmation_api.accepted.lock().unwrap(), vec![1]);
+        assert_eq!(poll.len(), 2);
+        assert!(matches!(&poll[0], PollEvent::NewSentOffer(offer) if offer.tradeofferid == 1));
+        assert!(matches!(&poll[1], PollEvent::AutoConfirmed(offer) if {
+            offer.trade_offer_state == TradeOfferState::Active
+        }));
+    }
 }
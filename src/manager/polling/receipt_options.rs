@@ -0,0 +1,68 @@
+use crate::api::TradeOfferApi;
+use crate::manager::session_renewer::SessionRenewer;
+use crate::error::Error;
+use crate::response::{Asset, TradeOffer};
+use chrono::Duration;
+
+const DEFAULT_ATTEMPTS: u32 = 5;
+const DEFAULT_RETRY_DELAY_SECONDS: i64 = 10;
+
+/// Options for fetching receipts for accepted offers during polls. Set
+/// [`PollOptions::fetch_receipts`](crate::polling::PollOptions::fetch_receipts) to enable.
+///
+/// Receipts are fetched in the background and sent as a
+/// [`PollEvent::OfferReceipt`](crate::polling::PollEvent::OfferReceipt) in a poll of their own once
+/// fetched, which can take up to `attempts` multiplied by `retry_delay` while waiting for trades to
+/// complete.
+#[derive(Debug, Clone, Copy)]
+pub struct ReceiptOptions {
+    /// The number of times to attempt fetching a receipt. Default is 5.
+    pub attempts: u32,
+    /// The duration to wait between attempts. Default is 10 seconds.
+    pub retry_delay: Duration,
+}
+
+impl Default for ReceiptOptions {
+    fn default() -> Self {
+        Self {
+            attempts: DEFAULT_ATTEMPTS,
+            // unwrap is safe because the value is in range
+            retry_delay: Duration::try_seconds(DEFAULT_RETRY_DELAY_SECONDS).unwrap(),
+        }
+    }
+}
+
+/// Gets the receipt for an accepted offer, retrying until the trade completes. The receipt is
+/// empty until the items are exchanged. Requests are retried once after renewing the session if
+/// we are not logged in.
+pub(crate) async fn get_receipt_with_retries(
+    api: &dyn TradeOfferApi,
+    session_renewer: &SessionRenewer,
+    offer: &TradeOffer,
+    options: ReceiptOptions,
+) -> Result<Vec<Asset>, Error> {
+    let tradeid = offer.tradeid
+        .ok_or(crate::error::ParameterError::MissingTradeId)?;
+    let retry_delay = options.retry_delay.to_std()
+        .unwrap_or(std::time::Duration::from_secs(DEFAULT_RETRY_DELAY_SECONDS as u64));
+    let mut result = Ok(Vec::new());
+    
+    for attempt in 1..=options.attempts.max(1) {
+        if attempt > 1 {
+            async_std::task::sleep(retry_delay).await;
+        }
+        
+        result = session_renewer.with_renewal(|| api.get_receipt(&tradeid)).await;
+        
+        match &result {
+            // The trade has completed.
+            Ok(assets) if !assets.is_empty() => break,
+            Ok(_) => log::debug!("Receipt for trade {tradeid} is empty on attempt {attempt}"),
+            Err(error) => log::debug!(
+                "Failed to get receipt for trade {tradeid} on attempt {attempt}: {error}"
+            ),
+        }
+    }
+    
+    result
+}
@@ -0,0 +1,70 @@
+use super::PollDataStore;
+use crate::manager::polling::PollData;
+use crate::helpers::write_file_atomic;
+use crate::error::{FileError, StoreError};
+use crate::SteamID;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use async_trait::async_trait;
+
+/// Stores poll data as JSON files in a directory. Each account is saved to
+/// `poll_data_<steamid>.json`. This is the default store used by the manager with the configured
+/// data directory.
+#[derive(Debug, Clone)]
+pub struct FilePollDataStore {
+    /// The directory to save poll data files to.
+    directory: PathBuf,
+}
+
+impl FilePollDataStore {
+    /// Creates a new [`FilePollDataStore`] which saves files to `directory`.
+    pub fn new<T>(directory: T) -> Self
+    where
+        T: Into<PathBuf>,
+    {
+        Self {
+            directory: directory.into(),
+        }
+    }
+    
+    /// The directory poll data files are saved to.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+    
+    fn filepath(&self, steamid: SteamID) -> PathBuf {
+        self.directory.join(format!("poll_data_{}.json", u64::from(steamid)))
+    }
+}
+
+#[async_trait]
+impl PollDataStore for FilePollDataStore {
+    async fn load(
+        &self,
+        steamid: SteamID,
+    ) -> Result<Option<PollData>, StoreError> {
+        let data = match async_fs::read_to_string(self.filepath(steamid)).await {
+            Ok(data) => data,
+            // Nothing has been saved for this account yet.
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(FileError::FileSystem(error).into()),
+        };
+        let poll_data = serde_json::from_str(&data)
+            .map_err(FileError::Parse)?;
+        
+        Ok(Some(poll_data))
+    }
+    
+    async fn save(
+        &self,
+        steamid: SteamID,
+        poll_data: &PollData,
+    ) -> Result<(), StoreError> {
+        let data = serde_json::to_string(poll_data)?;
+        
+        write_file_atomic(self.filepath(steamid), data.as_bytes()).await
+            .map_err(FileError::FileSystem)?;
+        
+        Ok(())
+    }
+}
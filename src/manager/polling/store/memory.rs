@@ -0,0 +1,46 @@
+use super::PollDataStore;
+use crate::manager::polling::PollData;
+use crate::error::StoreError;
+use crate::SteamID;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use async_trait::async_trait;
+
+/// Stores poll data in memory. Data is lost when the process exits, which makes this store
+/// useful for tests or for when state is persisted elsewhere. Clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryPollDataStore {
+    /// The poll data for each account.
+    data: Arc<RwLock<HashMap<SteamID, PollData>>>,
+}
+
+impl MemoryPollDataStore {
+    /// Creates a new empty [`MemoryPollDataStore`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Gets a copy of the poll data currently stored for `steamid`.
+    pub fn get(&self, steamid: SteamID) -> Option<PollData> {
+        self.data.read().unwrap().get(&steamid).cloned()
+    }
+}
+
+#[async_trait]
+impl PollDataStore for MemoryPollDataStore {
+    async fn load(
+        &self,
+        steamid: SteamID,
+    ) -> Result<Option<PollData>, StoreError> {
+        Ok(self.get(steamid))
+    }
+    
+    async fn save(
+        &self,
+        steamid: SteamID,
+        poll_data: &PollData,
+    ) -> Result<(), StoreError> {
+        self.data.write().unwrap().insert(steamid, poll_data.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+//! Storage backends for [`PollData`].
+
+mod file;
+mod memory;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+pub use file::FilePollDataStore;
+pub use memory::MemoryPollDataStore;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqlitePollDataStore;
+
+use super::PollData;
+use crate::error::StoreError;
+use crate::SteamID;
+use std::fmt::Debug;
+use async_trait::async_trait;
+
+/// A storage backend for [`PollData`]. Poll data holds the state of trade offers between polls
+/// so that changes can be detected across restarts.
+///
+/// By default the manager uses a [`FilePollDataStore`] in the data directory. Implement this
+/// trait to keep poll data elsewhere, such as in your own database.
+///
+/// # Examples
+/// ```
+/// use steam_tradeoffer_manager::SteamID;
+/// use steam_tradeoffer_manager::error::StoreError;
+/// use steam_tradeoffer_manager::polling::{PollData, PollDataStore};
+///
+/// #[derive(Debug)]
+/// struct NoopStore;
+///
+/// #[steam_tradeoffer_manager::async_trait]
+/// impl PollDataStore for NoopStore {
+///     async fn load(&self, _steamid: SteamID) -> Result<Option<PollData>, StoreError> {
+///         Ok(None)
+///     }
+///
+///     async fn save(&self, _steamid: SteamID, _poll_data: &PollData) -> Result<(), StoreError> {
+///         Ok(())
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait PollDataStore: Debug + Send + Sync {
+    /// Loads the poll data for `steamid`. Returns [`None`] if no data has been saved.
+    async fn load(
+        &self,
+        steamid: SteamID,
+    ) -> Result<Option<PollData>, StoreError>;
+    
+    /// Saves the poll data for `steamid`.
+    async fn save(
+        &self,
+        steamid: SteamID,
+        poll_data: &PollData,
+    ) -> Result<(), StoreError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::TradeOfferState;
+    
+    #[tokio::test]
+    async fn memory_store_saves_and_loads() {
+        let steamid = SteamID::try_from(76561198000000000).unwrap();
+        let store = MemoryPollDataStore::new();
+        let mut poll_data = PollData::default();
+        
+        assert!(store.load(steamid).await.unwrap().is_none());
+        
+        poll_data.state_map.insert(1, TradeOfferState::Active);
+        store.save(steamid, &poll_data).await.unwrap();
+        
+        let loaded = store.load(steamid).await.unwrap().unwrap();
+        
+        assert_eq!(loaded.state_map.get(&1), Some(&TradeOfferState::Active));
+    }
+    
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn sqlite_store_saves_and_loads() {
+        let steamid = SteamID::try_from(76561198000000000).unwrap();
+        let store = SqlitePollDataStore::open_in_memory().unwrap();
+        let mut poll_data = PollData::default();
+        
+        assert!(store.load(steamid).await.unwrap().is_none());
+        
+        poll_data.state_map.insert(1, TradeOfferState::Active);
+        store.save(steamid, &poll_data).await.unwrap();
+        poll_data.state_map.insert(1, TradeOfferState::Accepted);
+        store.save(steamid, &poll_data).await.unwrap();
+        
+        let loaded = store.load(steamid).await.unwrap().unwrap();
+        
+        assert_eq!(loaded.state_map.get(&1), Some(&TradeOfferState::Accepted));
+    }
+}
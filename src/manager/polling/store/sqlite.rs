@@ -0,0 +1,95 @@
+use super::PollDataStore;
+use crate::manager::polling::PollData;
+use crate::error::StoreError;
+use crate::SteamID;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use rusqlite::{Connection, OptionalExtension};
+
+/// Stores poll data in a SQLite database. Poll data is saved as JSON in the `poll_data` table,
+/// which is created if it does not exist.
+///
+/// Requires the `sqlite` feature.
+#[derive(Debug, Clone)]
+pub struct SqlitePollDataStore {
+    /// The database connection.
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqlitePollDataStore {
+    /// Opens (or creates) the database at `path`.
+    pub fn open<P>(path: P) -> Result<Self, StoreError>
+    where
+        P: AsRef<Path>,
+    {
+        Self::from_connection(Connection::open(path)?)
+    }
+    
+    /// Opens a database in memory.
+    pub fn open_in_memory() -> Result<Self, StoreError> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+    
+    /// Uses an existing connection.
+    pub fn from_connection(connection: Connection) -> Result<Self, StoreError> {
+        connection.execute(
+            "CREATE TABLE IF NOT EXISTS poll_data (
+                steamid INTEGER PRIMARY KEY NOT NULL,
+                data TEXT NOT NULL
+            )",
+            (),
+        )?;
+        
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+}
+
+#[async_trait]
+impl PollDataStore for SqlitePollDataStore {
+    async fn load(
+        &self,
+        steamid: SteamID,
+    ) -> Result<Option<PollData>, StoreError> {
+        let connection = Arc::clone(&self.connection);
+        let steamid = u64::from(steamid) as i64;
+        // SQLite is blocking so queries are performed outside of the async runtime's workers.
+        let data = tokio::task::spawn_blocking(move || {
+            connection.lock().unwrap().query_row(
+                "SELECT data FROM poll_data WHERE steamid = ?1",
+                [steamid],
+                |row| row.get::<_, String>(0),
+            ).optional()
+        }).await
+            .map_err(|error| StoreError::Other(error.into()))??;
+        
+        if let Some(data) = data {
+            return Ok(Some(serde_json::from_str(&data)?));
+        }
+        
+        Ok(None)
+    }
+    
+    async fn save(
+        &self,
+        steamid: SteamID,
+        poll_data: &PollData,
+    ) -> Result<(), StoreError> {
+        let connection = Arc::clone(&self.connection);
+        let steamid = u64::from(steamid) as i64;
+        let data = serde_json::to_string(poll_data)?;
+        
+        tokio::task::spawn_blocking(move || {
+            connection.lock().unwrap().execute(
+                "INSERT INTO poll_data (steamid, data) VALUES (?1, ?2)
+                ON CONFLICT(steamid) DO UPDATE SET data = excluded.data",
+                (steamid, data),
+            )
+        }).await
+            .map_err(|error| StoreError::Other(error.into()))??;
+        
+        Ok(())
+    }
+}
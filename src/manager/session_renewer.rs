@@ -0,0 +1,385 @@
+use crate::api::SteamTradeOfferAPI;
+use crate::auth::AuthClient;
+use crate::error::{AnyhowError, ConfirmationError, Error, ParameterError, Result};
+use crate::mobile_api::MobileAPI;
+use crate::session::SessionInfo;
+use crate::session_store::{SessionStore, StoredSession};
+use crate::time::ServerTime;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use futures::future::BoxFuture;
+use tokio_util::sync::CancellationToken;
+
+/// How long before the access token expires to renew it. This is before the warning about the
+/// session expiring.
+const RENEW_BEFORE_EXPIRY: Duration = Duration::from_secs(10 * 60);
+/// How long before the access token expires to warn that the session is expiring.
+const EXPIRY_WARNING: Duration = Duration::from_secs(5 * 60);
+/// How long a renewal is considered fresh. Requests that fail within this duration of a renewal
+/// do not renew again.
+const RENEWAL_COOLDOWN: Duration = Duration::from_secs(10);
+/// How long to wait before retrying a failed renewal.
+const RETRY_DELAY: Duration = Duration::from_secs(60);
+/// How long to wait before renewing if the expiry of the access token is unknown.
+const UNKNOWN_EXPIRY_DELAY: Duration = Duration::from_secs(60 * 60);
+
+type SessionExpiredFuture = BoxFuture<'static, std::result::Result<Vec<String>, AnyhowError>>;
+
+/// A callback that provides fresh cookies when the session expires.
+#[derive(Clone)]
+pub(crate) struct SessionExpiredHook(Arc<dyn Fn() -> SessionExpiredFuture + Send + Sync>);
+
+impl fmt::Debug for SessionExpiredHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SessionExpiredHook")
+    }
+}
+
+impl SessionExpiredHook {
+    /// Creates a new [`SessionExpiredHook`].
+    pub fn new<F, Fut>(hook: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<Vec<String>, AnyhowError>> + Send + 'static,
+    {
+        Self(Arc::new(move || Box::pin(hook())))
+    }
+}
+
+/// Renews the session using a refresh token or the session expired hook and saves it to the
+/// session store.
+#[derive(Debug)]
+pub(crate) struct SessionRenewer {
+    auth: AuthClient,
+    api: SteamTradeOfferAPI,
+    mobile_api: MobileAPI,
+    session_store: Option<SessionStore>,
+    /// Called for new cookies when the session cannot be renewed using the refresh token.
+    on_session_expired: Option<SessionExpiredHook>,
+    refresh_token: RwLock<Option<String>>,
+    /// The cookies the session was last set with. Saved alongside the refresh token.
+    cookies: RwLock<Option<Vec<String>>>,
+    /// The time of the last renewal. Held while renewing so concurrent failures renew once.
+    last_renewal: tokio::sync::Mutex<Option<Instant>>,
+    /// Held while saving the session so saves are written one at a time.
+    saving: tokio::sync::Mutex<()>,
+    /// The expiry of the access token that was last warned about. Used to warn once per token.
+    warned_expiry: Mutex<Option<ServerTime>>,
+}
+
+impl SessionRenewer {
+    /// Creates a new [`SessionRenewer`].
+    pub fn new(
+        auth: AuthClient,
+        api: SteamTradeOfferAPI,
+        mobile_api: MobileAPI,
+        session_store: Option<SessionStore>,
+        on_session_expired: Option<SessionExpiredHook>,
+    ) -> Self {
+        Self {
+            auth,
+            api,
+            mobile_api,
+            session_store,
+            on_session_expired,
+            refresh_token: RwLock::new(None),
+            cookies: RwLock::new(None),
+            last_renewal: tokio::sync::Mutex::new(None),
+            saving: tokio::sync::Mutex::new(()),
+            warned_expiry: Mutex::new(None),
+        }
+    }
+    
+    /// Gets the refresh token.
+    pub fn refresh_token(&self) -> Option<String> {
+        self.refresh_token.read().unwrap().clone()
+    }
+    
+    /// Sets the refresh token.
+    pub fn set_refresh_token(&self, refresh_token: String) {
+        *self.refresh_token.write().unwrap() = Some(refresh_token);
+    }
+    
+    /// Gets information about the current session decoded from the access token. Warns once per
+    /// access token when it is about to expire.
+    pub fn session_info(&self) -> Option<SessionInfo> {
+        let info = self.mobile_api.session.read().unwrap()
+            .as_ref()
+            .and_then(|session| session.info());
+        
+        if let Some(info) = &info {
+            self.warn_if_expiring(info);
+        }
+        
+        info
+    }
+    
+    /// Whether the session can be renewed, either using a refresh token or the session expired
+    /// hook.
+    pub fn can_renew(&self) -> bool {
+        self.on_session_expired.is_some() || self.refresh_token.read().unwrap().is_some()
+    }
+    
+    /// Saves the session to the session store, if one is set. `cookies` replaces the cookies that
+    /// were last set. Errors are logged rather than returned since the session is still usable.
+    pub async fn save_session(&self, cookies: Option<Vec<String>>) {
+        let Some(store) = &self.session_store else {
+            return;
+        };
+        
+        if cookies.is_some() {
+            *self.cookies.write().unwrap() = cookies;
+        }
+        
+        // The session is read after waiting for other saves so the latest session is written
+        // last.
+        let _saving = self.saving.lock().await;
+        let cookies = self.cookies.read().unwrap().clone();
+        let (Some(cookies), Ok(steamid)) = (cookies, self.mobile_api.get_steamid()) else {
+            // There is no session to save.
+            return;
+        };
+        let session = StoredSession {
+            steamid,
+            cookies,
+            refresh_token: self.refresh_token(),
+        };
+        
+        if let Err(error) = store.save(&session).await {
+            log::warn!("Failed to save session: {error}");
+        }
+    }
+    
+    /// Saves the session in the background. Used where the session is updated outside of an
+    /// async context. Nothing is saved if there is no tokio runtime. The cookies are kept and
+    /// saved with the next renewal.
+    pub fn spawn_save_session(self: &Arc<Self>, cookies: Option<Vec<String>>) {
+        if self.session_store.is_none() {
+            return;
+        }
+        
+        if cookies.is_some() {
+            // Set now so a save spawned afterwards cannot be overwritten with older cookies.
+            *self.cookies.write().unwrap() = cookies;
+        }
+        
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            log::warn!("Not saving session: no tokio runtime is running.");
+            return;
+        };
+        let renewer = Arc::clone(self);
+        
+        handle.spawn(async move { renewer.save_session(None).await });
+    }
+    
+    /// Renews the session and updates the cookies for both APIs. New cookies are requested using
+    /// the refresh token. If that is not possible, new cookies are requested from the
+    /// session expired hook. Skips renewing if the session was renewed moments ago, unless
+    /// `force` is `true`.
+    pub async fn renew(&self, force: bool) -> Result<()> {
+        let mut last_renewal = self.last_renewal.lock().await;
+        
+        if !force && last_renewal.is_some_and(|renewed| renewed.elapsed() < RENEWAL_COOLDOWN) {
+            return Ok(());
+        }
+        
+        let result = match self.refresh_token() {
+            Some(refresh_token) => self.renew_with_refresh_token(refresh_token).await,
+            None => Err(ParameterError::NoRefreshToken.into()),
+        };
+        
+        match (result, &self.on_session_expired) {
+            (Err(error), Some(on_session_expired)) => {
+                log::debug!("Access token not renewed ({error}); requesting new cookies");
+                
+                let cookies = (on_session_expired.0)().await
+                    .map_err(Error::SessionExpiredHook)?;
+                
+                self.api.set_cookies(cookies.clone())?;
+                self.mobile_api.set_cookies(cookies.clone())?;
+                self.save_session(Some(cookies)).await;
+                log::debug!("Set new cookies from session expired hook");
+            },
+            (result, _) => result?,
+        }
+        
+        *last_renewal = Some(Instant::now());
+        Ok(())
+    }
+    
+    /// Performs a request. If it fails because we are not logged in and the session can be
+    /// renewed, the session is renewed and the request is retried once.
+    pub async fn with_renewal<T, F, Fut>(
+        &self,
+        request: F,
+    ) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        // Warns if the session is about to expire.
+        self.session_info();
+        
+        match request().await {
+            result if is_logged_out(&result) && self.can_renew() => {
+                self.renew(false).await?;
+                request().await
+            },
+            result => result,
+        }
+    }
+    
+    /// Renews the session by exchanging the refresh token for new cookies.
+    async fn renew_with_refresh_token(&self, refresh_token: String) -> Result<()> {
+        let cookies = self.auth.get_web_cookies(&refresh_token).await?;
+        
+        self.api.set_cookies(cookies.clone())?;
+        self.mobile_api.set_cookies(cookies.clone())?;
+        self.save_session(Some(cookies)).await;
+        log::debug!("Renewed access token");
+        Ok(())
+    }
+    
+    /// Warns that the session is about to expire, once per access token.
+    fn warn_if_expiring(&self, info: &SessionInfo) {
+        let Some(time_until_expiry) = info.time_until_expiry() else {
+            return;
+        };
+        let mut warned_expiry = self.warned_expiry.lock().unwrap();
+        
+        if *warned_expiry == info.expires_at {
+            return;
+        }
+        
+        if time_until_expiry.to_std().unwrap_or_default() < EXPIRY_WARNING {
+            log::warn!(
+                "Session for {} expires in {} seconds; renew it or set new cookies",
+                u64::from(info.steamid),
+                time_until_expiry.num_seconds().max(0),
+            );
+            *warned_expiry = info.expires_at;
+        }
+    }
+    
+    /// Gets the duration until the access token should be renewed.
+    fn time_until_renewal(&self) -> Duration {
+        let Some(session) = self.mobile_api.session.read().unwrap().clone() else {
+            // Renew immediately to get a session from the refresh token.
+            return Duration::ZERO;
+        };
+        let Some(time_until_expiry) = session.info()
+            .and_then(|info| info.time_until_expiry()) else {
+            return UNKNOWN_EXPIRY_DELAY;
+        };
+        
+        time_until_expiry
+            .to_std()
+            .unwrap_or_default()
+            .saturating_sub(RENEW_BEFORE_EXPIRY)
+    }
+}
+
+/// Whether a request failed because we are not logged in.
+pub(crate) fn is_logged_out<T>(
+    result: &Result<T>,
+) -> bool {
+    matches!(result, Err(Error::NotLoggedIn | Error::Confirmation(ConfirmationError::NeedsAuth)))
+}
+
+/// Spawns the task that renews the access token before it expires. The task ends when cancelled.
+pub(crate) fn spawn_session_renewal(
+    renewer: Arc<SessionRenewer>,
+    cancellation_token: CancellationToken,
+) {
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                // Ends the task.
+                _ = cancellation_token.cancelled() => return,
+                _ = async_std::task::sleep(renewer.time_until_renewal()) => {},
+            }
+            
+            let delay = match renewer.renew(true).await {
+                Ok(()) => continue,
+                Err(error) => {
+                    log::warn!("Failed to renew access token: {error}");
+                    RETRY_DELAY
+                },
+            };
+            
+            tokio::select! {
+                _ = cancellation_token.cancelled() => return,
+                _ = async_std::task::sleep(delay) => {},
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jwt::tests::encode_jwt;
+    
+    fn renewer(on_session_expired: Option<SessionExpiredHook>) -> SessionRenewer {
+        let data_directory = std::env::temp_dir().join("session_renewer_test");
+        
+        SessionRenewer::new(
+            AuthClient::builder().build(),
+            SteamTradeOfferAPI::builder().data_directory(data_directory).build(),
+            MobileAPI::builder().build(),
+            None,
+            on_session_expired,
+        )
+    }
+    
+    #[tokio::test]
+    async fn renews_using_session_expired_hook() {
+        let renewer = renewer(Some(SessionExpiredHook::new(|| async {
+            Ok(vec!["steamLoginSecure=76561198000000000%7C%7Ctoken".to_string()])
+        })));
+        
+        assert!(renewer.can_renew());
+        
+        renewer.renew(false).await.unwrap();
+        
+        assert_eq!(u64::from(renewer.mobile_api.get_steamid().unwrap()), 76561198000000000);
+        assert!(renewer.api.session.read().unwrap().is_some());
+    }
+    
+    #[test]
+    fn warns_when_access_token_is_about_to_expire() {
+        let renewer = renewer(None);
+        let set_expiry = |seconds: i64| {
+            let exp = crate::time::get_server_time_now().timestamp() + seconds;
+            let token = encode_jwt(&format!(r#"{{"sub":"76561198000000000","exp":{exp}}}"#));
+            
+            renewer.mobile_api
+                .set_cookies(vec![format!("steamLoginSecure=76561198000000000%7C%7C{token}")])
+                .unwrap();
+        };
+        
+        set_expiry(60 * 60);
+        renewer.session_info().unwrap();
+        
+        assert!(renewer.warned_expiry.lock().unwrap().is_none());
+        
+        set_expiry(60);
+        
+        let info = renewer.session_info().unwrap();
+        
+        assert_eq!(*renewer.warned_expiry.lock().unwrap(), info.expires_at);
+    }
+    
+    #[tokio::test]
+    async fn cannot_renew_without_refresh_token_or_hook() {
+        let renewer = renewer(None);
+        
+        assert!(!renewer.can_renew());
+        assert!(matches!(
+            renewer.renew(false).await,
+            Err(Error::Parameter(ParameterError::NoRefreshToken)),
+        ));
+    }
+}
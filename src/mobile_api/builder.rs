@@ -1,5 +1,5 @@
 use super::MobileAPI;
-use crate::helpers::USER_AGENT_STRING;
+use crate::helpers::{parse_base_url, DEFAULT_COMMUNITY_URL, USER_AGENT_STRING};
 use crate::session::Session;
 use std::sync::{Arc, RwLock};
 use reqwest::cookie::Jar;
@@ -20,6 +20,8 @@ pub struct MobileAPIBuilder {
     pub(crate) time_offset: i64,
     /// The session.
     pub(crate) session: Option<Arc<RwLock<Option<Session>>>>,
+    /// The base URL for Steam community requests.
+    pub(crate) community_url: String,
 }
 
 impl Default for MobileAPIBuilder {
@@ -31,6 +33,7 @@ impl Default for MobileAPIBuilder {
             user_agent: USER_AGENT_STRING,
             time_offset: 0,
             session: None,
+            community_url: DEFAULT_COMMUNITY_URL.into(),
         }
     }
 }
@@ -61,6 +64,17 @@ impl MobileAPIBuilder {
         self
     }
     
+    /// The base URL for Steam community requests, including the scheme and optionally a port e.g.
+    /// `http://127.0.0.1:8080`. Defaults to `https://steamcommunity.com`.
+    /// Panics if the URL is not valid.
+    pub fn community_url<T>(mut self, community_url: T) -> Self
+    where
+        T: Into<String>,
+    {
+        self.community_url = parse_base_url(community_url.into());
+        self
+    }
+    
     /// Sets the session.
     pub(crate) fn session(mut self, session: Arc<RwLock<Option<Session>>>) -> Self {
         self.session = Some(session);
@@ -0,0 +1,214 @@
+use super::MobileAPI;
+use crate::error::{ParameterError, Result};
+use crate::response::{Confirmation, ConfirmationDetails};
+use std::fmt::Debug;
+use async_trait::async_trait;
+
+/// The requests the [`TradeOfferManager`](crate::TradeOfferManager) makes for mobile
+/// confirmations. [`MobileAPI`] is the default implementation.
+///
+/// Implement this trait to replace the requests with fakes in tests, or to wrap the default API
+/// with decorators. Set it using
+/// [`TradeOfferManagerBuilder::confirmation_api`](crate::TradeOfferManagerBuilder::confirmation_api).
+///
+/// # Examples
+/// ```
+/// use steam_tradeoffer_manager::error::Error;
+/// use steam_tradeoffer_manager::mobile_api::ConfirmationApi;
+/// use steam_tradeoffer_manager::response::{Confirmation, ConfirmationDetails};
+///
+/// // An account without any pending confirmations.
+/// #[derive(Debug)]
+/// struct NoConfirmations;
+///
+/// #[steam_tradeoffer_manager::async_trait]
+/// impl ConfirmationApi for NoConfirmations {
+///     async fn get_trade_confirmations(
+///         &self,
+///     ) -> Result<Vec<Confirmation>, Error> {
+///         Ok(Vec::new())
+///     }
+///
+///     async fn get_confirmation_details(
+///         &self,
+///         _confirmation: &Confirmation,
+///     ) -> Result<ConfirmationDetails, Error> {
+///         Ok(ConfirmationDetails::Other)
+///     }
+///
+///     async fn accept_confirmation(
+///         &self,
+///         _confirmation: &Confirmation,
+///     ) -> Result<(), Error> {
+///         Ok(())
+///     }
+///
+///     async fn cancel_confirmation(
+///         &self,
+///         _confirmation: &Confirmation,
+///     ) -> Result<(), Error> {
+///         Ok(())
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait ConfirmationApi: Debug + Send + Sync {
+    /// Gets the trade confirmations.
+    async fn get_trade_confirmations(
+        &self,
+    ) -> Result<Vec<Confirmation>>;
+    
+    /// Gets the details for a confirmation.
+    async fn get_confirmation_details(
+        &self,
+        confirmation: &Confirmation,
+    ) -> Result<ConfirmationDetails>;
+    
+    /// Accepts a confirmation.
+    async fn accept_confirmation(
+        &self,
+        confirmation: &Confirmation,
+    ) -> Result<()>;
+    
+    /// Cancels a confirmation.
+    async fn cancel_confirmation(
+        &self,
+        confirmation: &Confirmation,
+    ) -> Result<()>;
+    
+    /// Checks that the credentials needed to respond to confirmations are set. Called before
+    /// polling starts. Offers are not confirmed during polls if this fails. By default, no
+    /// credentials are required. Decorators should delegate this to the API they wrap.
+    fn check_credentials(
+        &self,
+    ) -> Result<()> {
+        Ok(())
+    }
+    
+    /// Accepts confirmations. Returns a result for each confirmation in the same order as
+    /// `confirmations`. By default, confirmations are accepted one at a time.
+    async fn accept_confirmations(
+        &self,
+        confirmations: &[Confirmation],
+    ) -> Vec<Result<()>> {
+        let mut results = Vec::with_capacity(confirmations.len());
+        
+        for confirmation in confirmations {
+            results.push(self.accept_confirmation(confirmation).await);
+        }
+        
+        results
+    }
+    
+    /// Cancels confirmations. Returns a result for each confirmation in the same order as
+    /// `confirmations`. By default, confirmations are cancelled one at a time.
+    async fn cancel_confirmations(
+        &self,
+        confirmations: &[Confirmation],
+    ) -> Vec<Result<()>> {
+        let mut results = Vec::with_capacity(confirmations.len());
+        
+        for confirmation in confirmations {
+            results.push(self.cancel_confirmation(confirmation).await);
+        }
+        
+        results
+    }
+}
+
+#[async_trait]
+impl ConfirmationApi for MobileAPI {
+    async fn get_trade_confirmations(
+        &self,
+    ) -> Result<Vec<Confirmation>> {
+        MobileAPI::get_trade_confirmations(self).await
+    }
+    
+    async fn get_confirmation_details(
+        &self,
+        confirmation: &Confirmation,
+    ) -> Result<ConfirmationDetails> {
+        MobileAPI::get_confirmation_details(self, confirmation).await
+    }
+    
+    async fn accept_confirmation(
+        &self,
+        confirmation: &Confirmation,
+    ) -> Result<()> {
+        MobileAPI::accept_confirmation(self, confirmation).await
+    }
+    
+    async fn cancel_confirmation(
+        &self,
+        confirmation: &Confirmation,
+    ) -> Result<()> {
+        MobileAPI::cancel_confirmation(self, confirmation).await
+    }
+    
+    async fn accept_confirmations(
+        &self,
+        confirmations: &[Confirmation],
+    ) -> Vec<Result<()>> {
+        MobileAPI::accept_confirmations(self, confirmations).await
+    }
+    
+    async fn cancel_confirmations(
+        &self,
+        confirmations: &[Confirmation],
+    ) -> Vec<Result<()>> {
+        MobileAPI::cancel_confirmations(self, confirmations).await
+    }
+    
+    fn check_credentials(
+        &self,
+    ) -> Result<()> {
+        if self.identity_secret.is_none() {
+            return Err(ParameterError::NoIdentitySecret.into());
+        }
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TradeOfferManager;
+    use crate::error::Error;
+    use crate::fixtures::FakeConfirmationApi;
+    use crate::polling::{ConfirmationEvent, ConfirmationPollOptions};
+    
+    const COOKIES: [&str; 2] = [
+        "sessionid=sessionid",
+        "steamLoginSecure=76561198000000000%7C%7Ctoken",
+    ];
+    
+    #[tokio::test]
+    async fn injected_api_does_not_need_identity_secret() {
+        let cookies = COOKIES.map(String::from).to_vec();
+        let manager = TradeOfferManager::builder()
+            .cookies(cookies.clone())
+            .build();
+        
+        assert!(matches!(
+            manager.start_confirmation_polling(ConfirmationPollOptions::default()),
+            Err(Error::Parameter(ParameterError::NoIdentitySecret)),
+        ));
+        
+        let manager = TradeOfferManager::builder()
+            .cookies(cookies)
+            .confirmation_api(|_api| FakeConfirmationApi {
+                tradeofferids: vec![1],
+                ..Default::default()
+            })
+            .build();
+        let mut receiver = manager
+            .start_confirmation_polling(ConfirmationPollOptions::default())
+            .unwrap();
+        
+        assert!(matches!(
+            receiver.recv().await,
+            Some(ConfirmationEvent::New(confirmation)) if confirmation.id == 1,
+        ));
+    }
+}
@@ -6,25 +6,33 @@
 // modifications to fit with the rest of this crate.
 
 mod builder;
+mod confirmation_api;
 mod operation;
+mod response;
+mod time_offset;
 
 pub use builder::MobileAPIBuilder;
+pub use confirmation_api::ConfirmationApi;
+pub use time_offset::TimeSyncOptions;
 use operation::Operation;
+use response::ConfirmationStatus;
+pub(crate) use time_offset::spawn_time_sync;
+use time_offset::TimeOffset;
 
 use crate::SteamID;
-use crate::error::{Error, ParameterError, Result, SetCookiesError};
+use crate::error::{ConfirmationError, Error, ParameterError, Result, SetCookiesError};
 use crate::helpers::{
     get_default_client,
     get_session_from_cookies,
+    parses_html_response,
     parses_response,
-    COMMUNITY_HOSTNAME,
 };
 use crate::session::Session;
-use crate::response::Confirmation;
+use crate::response::{Confirmation, ConfirmationDetails};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use std::sync::atomic::{AtomicU64, Ordering};
-use another_steam_totp::{generate_confirmation_key, get_device_id, Tag};
+use another_steam_totp::{generate_confirmation_key, get_device_id, get_steam_server_time_offset, Tag};
 use reqwest::cookie::Jar;
 use reqwest_middleware::ClientWithMiddleware;
 use serde::Deserialize;
@@ -35,8 +43,12 @@ use url::Url;
 pub struct MobileAPI {
     /// The identity secret for mobile confirmations.
     pub identity_secret: Option<String>,
-    /// The time offset from Steam's servers.
+    /// How many seconds we are behind Steam's servers. Only used until the offset is set using
+    /// [`MobileAPI::set_time_offset`] or synced.
+    #[deprecated(note = "use `time_offset()` and `set_time_offset()`, which are shared between clones")]
     pub time_offset: i64,
+    /// How many seconds we are behind Steam's servers, once set or synced. Shared between clones.
+    synced_time_offset: Arc<TimeOffset>,
     /// The session.
     pub(crate) session: Arc<RwLock<Option<Session>>>,
     /// The client for making requests.
@@ -46,12 +58,11 @@ pub struct MobileAPI {
     cookies: Arc<Jar>,
     /// The SteamID of the logged in user. `0` if no login cookies were passed.
     steamid: Arc<AtomicU64>,
+    /// The base URL for Steam community requests.
+    community_url: String,
 }
 
 impl MobileAPI {
-    /// Hostname for requests.
-    const HOSTNAME: &'static str = COMMUNITY_HOSTNAME;
-    
     /// Builder for constructing a [`MobileAPI`].
     pub fn builder() -> MobileAPIBuilder {
         MobileAPIBuilder::new()
@@ -66,9 +77,11 @@ impl MobileAPI {
         mut cookies: Vec<String>,
     ) -> std::result::Result<(), SetCookiesError> {
         let session = get_session_from_cookies(&mut cookies)?;
-        // Should not panic since the URL is hardcoded.
-        let url = format!("https://{}", Self::HOSTNAME).parse::<Url>()
-            .unwrap_or_else(|error| panic!("URL could not be parsed from {}: {}", Self::HOSTNAME, error));
+        // Should not panic since the URL is checked when building.
+        let url = self.community_url.parse::<Url>()
+            .unwrap_or_else(|error| {
+                panic!("URL could not be parsed from {}: {}", self.community_url, error)
+            });
         
         // The session contains steamid but an Atomicu64 is faster to access.
         self.steamid.store(session.steamid, Ordering::Relaxed);
@@ -81,6 +94,30 @@ impl MobileAPI {
         Ok(())
     }
     
+    /// Gets how many seconds we are behind Steam's servers.
+    #[allow(deprecated)]
+    pub fn time_offset(&self) -> i64 {
+        self.synced_time_offset.get().unwrap_or(self.time_offset)
+    }
+    
+    /// Sets how many seconds we are behind Steam's servers. Used in mobile confirmations. This is
+    /// shared with all clones of this [`MobileAPI`].
+    pub fn set_time_offset(&self, time_offset: i64) {
+        self.synced_time_offset.set(time_offset);
+    }
+    
+    /// Fetches the time from Steam's servers using [`get_steam_server_time_offset`] and updates
+    /// the time offset. Returns the new offset. The time is always fetched from
+    /// `api.steampowered.com`.
+    pub async fn sync_time_offset(
+        &self,
+    ) -> Result<i64> {
+        let time_offset = get_steam_server_time_offset().await?;
+        
+        self.synced_time_offset.set(time_offset);
+        Ok(time_offset)
+    }
+    
     /// Accepts a confirmation.
     pub async fn accept_confirmation(
         &self,
@@ -97,6 +134,36 @@ impl MobileAPI {
         self.send_confirmation_ajax(confirmation.id, confirmation.nonce, Operation::Cancel).await
     }
     
+    /// Accepts confirmations. Confirmations that can be confirmed together
+    /// ([`Confirmation::multi`]) are sent in a single request and the rest are sent one at a time.
+    /// If a batch request fails without telling whether it was applied, its confirmations are
+    /// retried one at a time to determine which failed. If the session expired, the confirmation
+    /// key was rejected or requests are being rate limited, that error is returned for every
+    /// confirmation without sending any more requests.
+    /// 
+    /// Returns a result for each confirmation in the same order as `confirmations`.
+    pub async fn accept_confirmations(
+        &self,
+        confirmations: &[Confirmation],
+    ) -> Vec<Result<()>> {
+        self.send_confirmations(confirmations, Operation::Allow).await
+    }
+    
+    /// Cancels confirmations. Confirmations that can be cancelled together
+    /// ([`Confirmation::multi`]) are sent in a single request and the rest are sent one at a time.
+    /// If a batch request fails without telling whether it was applied, its confirmations are
+    /// retried one at a time to determine which failed. If the session expired, the confirmation
+    /// key was rejected or requests are being rate limited, that error is returned for every
+    /// confirmation without sending any more requests.
+    /// 
+    /// Returns a result for each confirmation in the same order as `confirmations`.
+    pub async fn cancel_confirmations(
+        &self,
+        confirmations: &[Confirmation],
+    ) -> Vec<Result<()>> {
+        self.send_confirmations(confirmations, Operation::Cancel).await
+    }
+    
     /// Accepts a confirmation by ID.
     pub async fn accept_confirmation_by_id(
         &self,
@@ -121,24 +188,51 @@ impl MobileAPI {
     ) -> Result<Vec<Confirmation>> {
         #[derive(Deserialize)]
         pub struct GetTradeConfirmationsResponse {
-            // #[serde(default)]
-            // pub success: bool,
+            #[serde(flatten)]
+            pub status: ConfirmationStatus,
             #[serde(default)]
             pub conf: Vec<Confirmation>,
         }
         
-        let uri = Self::get_url("/mobileconf/getlist");
+        let uri = self.get_url("/mobileconf/getlist");
         let query = self.get_confirmation_query_params(Tag::Conf)?;
         let response = self.client.get(&uri)
             .header("X-Requested-With", "com.valvesoftware.android.steam.community")
             .query(&query)
             .send()
             .await?;
-        let response: GetTradeConfirmationsResponse = parses_response(response).await?;
+        let response: GetTradeConfirmationsResponse = parses_response(response).await
+            .inspect_err(|error| self.check_time_offset(error))?;
+        
+        if let Err(error) = response.status.check(true) {
+            let error = Error::Confirmation(error);
+            
+            self.check_time_offset(&error);
+            return Err(error);
+        }
         
         Ok(response.conf)
     }
     
+    /// Gets the details for a confirmation from its details page. Use this to check that a
+    /// confirmation is for the trade offer or market listing you expect before accepting it.
+    pub async fn get_confirmation_details(
+        &self,
+        confirmation: &Confirmation,
+    ) -> Result<ConfirmationDetails> {
+        let uri = self.get_url(&format!("/mobileconf/detailspage/{}", confirmation.id));
+        let query = self.get_confirmation_query_params(Tag::Details)?;
+        let response = self.client.get(&uri)
+            .header("X-Requested-With", "com.valvesoftware.android.steam.community")
+            .query(&query)
+            .send()
+            .await?;
+        let body = parses_html_response(response).await?;
+        let details = ConfirmationDetails::parse(&body, confirmation.r#type)?;
+        
+        Ok(details)
+    }
+    
     fn get_confirmation_query_params(
         &self,
         tag: Tag,
@@ -146,7 +240,7 @@ impl MobileAPI {
         let steamid = self.get_steamid()?;
         let identity_secret = self.identity_secret.as_ref()
             .ok_or(ParameterError::NoIdentitySecret)?;
-        let time_offset = Some(self.time_offset);
+        let time_offset = Some(self.time_offset());
         let (key, time) = generate_confirmation_key(identity_secret, tag, time_offset)?;
         let mut params: HashMap<&'static str, String> = HashMap::new();
         let device_id = get_device_id(u64::from(steamid));
@@ -167,34 +261,141 @@ impl MobileAPI {
         nonce: u64,
         operation: Operation,
     ) -> Result<()>  {
-        #[derive(Deserialize)]
-        struct SendConfirmationResponse {
-            pub success: bool,
-            #[serde(default)]
-            pub message: Option<String>,
-        }
-        
         let mut query = self.get_confirmation_query_params(Tag::Conf)?;
         
         query.insert("op", operation.to_string());
         query.insert("cid", id.to_string());
         query.insert("ck", nonce.to_string());
         
-        let uri = Self::get_url("/mobileconf/ajaxop");
+        let uri = self.get_url("/mobileconf/ajaxop");
         let response = self.client.get(&uri)
             .header("X-Requested-With", "com.valvesoftware.android.steam.community")
             .query(&query)
             .send()
             .await?;
-        let body: SendConfirmationResponse = parses_response(response).await?;
+        let body: ConfirmationStatus = parses_response(response).await
+            .inspect_err(|error| self.check_time_offset(error))?;
         
-        if !body.success {
-            return Err(Error::ConfirmationUnsuccessful(body.message));
+        if let Err(error) = body.check(false) {
+            let error = Error::Confirmation(error);
+            
+            self.check_time_offset(&error);
+            return Err(error);
         }
         
         Ok(())
     }
     
+    async fn send_confirmations(
+        &self,
+        confirmations: &[Confirmation],
+        operation: Operation,
+    ) -> Vec<Result<()>> {
+        let mut results = confirmations
+            .iter()
+            .map(|_| None)
+            .collect::<Vec<Option<Result<()>>>>();
+        let (
+            multi,
+            mut single,
+        ): (Vec<_>, Vec<_>) = (0..confirmations.len())
+            .partition(|index| confirmations[*index].multi);
+        
+        // A batch of one is sent on its own.
+        if multi.len() > 1 {
+            let batch = multi
+                .iter()
+                .map(|index| &confirmations[*index])
+                .collect::<Vec<_>>();
+            
+            match self.send_multi_confirmation_ajax(&batch, operation).await {
+                Ok(()) => for index in multi {
+                    results[index] = Some(Ok(()));
+                },
+                // Every other confirmation would fail in the same way.
+                Err(error) if copy_batch_error(&error).is_some() => {
+                    log::debug!("Batch confirmation failed: {error}");
+                    
+                    // Copies the error for each confirmation.
+                    return std::iter::successors(Some(error), copy_batch_error)
+                        .take(confirmations.len())
+                        .map(Err)
+                        .collect();
+                },
+                Err(error) => {
+                    log::debug!("Batch confirmation failed, retrying individually: {error}");
+                    single.extend(multi);
+                    single.sort_unstable();
+                },
+            }
+        } else {
+            single.extend(multi);
+        }
+        
+        for index in single {
+            let confirmation = &confirmations[index];
+            let result = self.send_confirmation_ajax(
+                confirmation.id,
+                confirmation.nonce,
+                operation,
+            ).await;
+            
+            results[index] = Some(result);
+        }
+        
+        results
+            .into_iter()
+            // Every index is filled above.
+            .map(|result| result.unwrap_or(Ok(())))
+            .collect()
+    }
+    
+    async fn send_multi_confirmation_ajax(
+        &self,
+        confirmations: &[&Confirmation],
+        operation: Operation,
+    ) -> Result<()> {
+        let mut form = self.get_confirmation_query_params(operation.tag())?
+            .into_iter()
+            .collect::<Vec<_>>();
+        
+        form.push(("op", operation.to_string()));
+        
+        for confirmation in confirmations {
+            form.push(("cid[]", confirmation.id.to_string()));
+            form.push(("ck[]", confirmation.nonce.to_string()));
+        }
+        
+        let uri = self.get_url("/mobileconf/multiajaxop");
+        let response = self.client.post(&uri)
+            .header("X-Requested-With", "com.valvesoftware.android.steam.community")
+            .form(&form)
+            .send()
+            .await?;
+        let body: ConfirmationStatus = parses_response(response).await
+            .inspect_err(|error| self.check_time_offset(error))?;
+        
+        if let Err(error) = body.check(false) {
+            let error = Error::Confirmation(error);
+            
+            self.check_time_offset(&error);
+            return Err(error);
+        }
+        
+        Ok(())
+    }
+    
+    /// Requests the time offset to be synced again if the confirmation key was rejected, which
+    /// may be caused by the clock drifting from Steam's servers.
+    fn check_time_offset(
+        &self,
+        error: &Error,
+    ) {
+        if matches!(error, Error::Confirmation(ConfirmationError::InvalidAuthenticator { .. })) {
+            self.synced_time_offset.request_resync();
+        }
+    }
+    
     /// Gets the logged-in user's SteamID.
     pub fn get_steamid(
         &self,
@@ -213,9 +414,30 @@ impl MobileAPI {
     }
     
     fn get_url(
+        &self,
         pathname: &str,
     ) -> String {
-        format!("https://{}{pathname}", Self::HOSTNAME)
+        format!("{}{pathname}", self.community_url)
+    }
+}
+
+/// Copies an error from a batch confirmation request that applies to every confirmation, i.e. the
+/// session expired, the confirmation key was rejected or requests are being rate limited.
+/// Retrying confirmations one at a time would fail in the same way. [`None`] for errors that don't
+/// tell whether the batch was applied.
+fn copy_batch_error(
+    error: &Error,
+) -> Option<Error> {
+    match error {
+        Error::NotLoggedIn => Some(Error::NotLoggedIn),
+        Error::Confirmation(error @ (
+            ConfirmationError::NeedsAuth |
+            ConfirmationError::InvalidAuthenticator { .. }
+        )) => Some(Error::Confirmation(error.clone())),
+        Error::StatusCode(
+            status
+        ) if *status == reqwest::StatusCode::TOO_MANY_REQUESTS => Some(Error::StatusCode(*status)),
+        _ => None,
     }
 }
 
@@ -237,7 +459,50 @@ impl From<MobileAPIBuilder> for MobileAPI {
             session,
             identity_secret: builder.identity_secret,
             steamid: Arc::new(AtomicU64::new(0)),
+            #[allow(deprecated)]
             time_offset: builder.time_offset,
+            synced_time_offset: Arc::new(TimeOffset::default()),
+            community_url: builder.community_url,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn copies_batch_errors_that_apply_to_every_confirmation() {
+        assert!(matches!(copy_batch_error(&Error::NotLoggedIn), Some(Error::NotLoggedIn)));
+        assert!(matches!(
+            copy_batch_error(&Error::Confirmation(ConfirmationError::NeedsAuth)),
+            Some(Error::Confirmation(ConfirmationError::NeedsAuth)),
+        ));
+        assert!(matches!(
+            copy_batch_error(&Error::StatusCode(reqwest::StatusCode::TOO_MANY_REQUESTS)),
+            Some(Error::StatusCode(reqwest::StatusCode::TOO_MANY_REQUESTS)),
+        ));
+        // These don't tell whether the batch was applied.
+        assert!(copy_batch_error(&Error::ResponseUnsuccessful).is_none());
+        assert!(copy_batch_error(&Error::StatusCode(reqwest::StatusCode::BAD_GATEWAY)).is_none());
+        assert!(copy_batch_error(&Error::Confirmation(ConfirmationError::Unsuccessful {
+            message: None,
+            detail: None,
+        })).is_none());
+    }
+    
+    #[test]
+    #[allow(deprecated)]
+    fn uses_built_time_offset_until_set() {
+        let mobile_api = MobileAPI::builder().time_offset(5).build();
+        let clone = mobile_api.clone();
+        
+        assert_eq!(mobile_api.time_offset, 5);
+        assert_eq!(mobile_api.time_offset(), 5);
+        
+        mobile_api.set_time_offset(10);
+        
+        assert_eq!(clone.time_offset(), 10);
+        assert_eq!(clone.time_offset, 5);
+    }
+}
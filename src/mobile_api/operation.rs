@@ -1,4 +1,5 @@
 use std::fmt;
+use another_steam_totp::Tag;
 
 /// Operation to act on confirmations.
 #[derive(Debug, Clone, Copy)]
@@ -9,6 +10,16 @@ pub enum Operation {
     Cancel,
 }
 
+impl Operation {
+    /// The tag used to generate the confirmation key for this operation.
+    pub fn tag(&self) -> Tag {
+        match self {
+            Self::Allow => Tag::Allow,
+            Self::Cancel => Tag::Cancel,
+        }
+    }
+}
+
 impl fmt::Display for Operation {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
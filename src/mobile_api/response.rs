@@ -0,0 +1,126 @@
+use crate::error::ConfirmationError;
+use serde::Deserialize;
+
+/// Phrases in the `message` or `detail` of unsuccessful responses which mean the confirmation key
+/// was rejected. Steam does not include a code for this in `/mobileconf` responses, so the text is
+/// matched case-insensitively. The same response is returned whether the identity secret is
+/// invalid or the clock is off, so the two cannot be told apart.
+const INVALID_AUTHENTICATOR_PHRASES: &[&str] = &[
+    // "Invalid authenticator"
+    "invalid authenticator",
+    // "It looks like your Steam Guard Mobile Authenticator is providing incorrect Steam Guard
+    // codes. This could be caused by an inaccurate clock or bad timezone settings on your
+    // device..."
+    "providing incorrect steam guard codes",
+];
+
+/// The status fields included in responses from `/mobileconf` endpoints.
+#[derive(Debug, Deserialize)]
+pub(crate) struct ConfirmationStatus {
+    #[serde(default)]
+    pub success: Option<bool>,
+    #[serde(default)]
+    pub needauth: bool,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+impl ConfirmationStatus {
+    /// Checks the status for errors. A missing `success` field is treated as unsuccessful unless
+    /// `allow_missing_success` is `true`.
+    pub fn check(
+        self,
+        allow_missing_success: bool,
+    ) -> Result<(), ConfirmationError> {
+        if self.needauth {
+            return Err(ConfirmationError::NeedsAuth);
+        }
+        
+        if self.success.unwrap_or(allow_missing_success) {
+            return Ok(());
+        }
+        
+        let message = self.message.filter(|message| !message.is_empty());
+        let detail = self.detail.filter(|detail| !detail.is_empty());
+        let is_invalid_authenticator = [&message, &detail]
+            .into_iter()
+            .flatten()
+            .any(|text| is_invalid_authenticator(text));
+        
+        if is_invalid_authenticator {
+            Err(ConfirmationError::InvalidAuthenticator {
+                message,
+                detail,
+            })
+        } else {
+            Err(ConfirmationError::Unsuccessful {
+                message,
+                detail,
+            })
+        }
+    }
+}
+
+/// Checks whether the text from a response means the confirmation key was rejected.
+fn is_invalid_authenticator(
+    text: &str,
+) -> bool {
+    let text = text.to_lowercase();
+    
+    INVALID_AUTHENTICATOR_PHRASES
+        .iter()
+        .any(|phrase| text.contains(phrase))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn check(json: &str) -> Result<(), ConfirmationError> {
+        serde_json::from_str::<ConfirmationStatus>(json).unwrap().check(false)
+    }
+    
+    #[test]
+    fn classifies_confirmation_errors() {
+        assert_eq!(check(r#"{"success":true}"#), Ok(()));
+        assert_eq!(
+            check(r#"{"success":false,"needauth":true}"#),
+            Err(ConfirmationError::NeedsAuth),
+        );
+        assert!(matches!(
+            check(r#"{"success":false,"message":"Invalid authenticator","detail":""}"#),
+            Err(ConfirmationError::InvalidAuthenticator { detail: None, .. })
+        ));
+        assert_eq!(check(r#"{"success":false}"#), Err(ConfirmationError::Unsuccessful {
+            message: None,
+            detail: None,
+        }));
+        assert!(check("{}").is_err());
+    }
+    
+    #[test]
+    fn classifies_invalid_authenticator_messages() {
+        assert!(matches!(
+            check(r#"{"success":false,"message":"Invalid authenticator"}"#),
+            Err(ConfirmationError::InvalidAuthenticator { .. })
+        ));
+        assert!(matches!(
+            check(r#"{
+                "success":false,
+                "message":"Oh nooooooes!",
+                "detail":"It looks like your Steam Guard Mobile Authenticator is providing incorrect Steam Guard codes. This could be caused by an inaccurate clock or bad timezone settings on your device."
+            }"#),
+            Err(ConfirmationError::InvalidAuthenticator { .. })
+        ));
+        // Mentioning the authenticator alone does not mean the key was rejected.
+        assert!(matches!(
+            check(r#"{
+                "success":false,
+                "message":"You must have a Steam Guard Mobile Authenticator to confirm trades."
+            }"#),
+            Err(ConfirmationError::Unsuccessful { .. })
+        ));
+    }
+}
@@ -0,0 +1,108 @@
+use super::MobileAPI;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use chrono::Duration;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+const DEFAULT_SYNC_INTERVAL_SECONDS: i64 = 60 * 60;
+const DEFAULT_MIN_RESYNC_INTERVAL_SECONDS: i64 = 60;
+
+/// How many seconds we are behind Steam's servers. Shared between clones of [`MobileAPI`] so
+/// that every clone uses the synced offset.
+#[derive(Debug, Default)]
+pub(crate) struct TimeOffset {
+    offset: AtomicI64,
+    /// Whether the offset was set or synced.
+    is_set: AtomicBool,
+    /// Notified when a confirmation request fails in a way that may be caused by clock drift.
+    resync: Notify,
+}
+
+impl TimeOffset {
+    /// Gets the offset. [`None`] if it has not been set or synced yet.
+    pub fn get(&self) -> Option<i64> {
+        self.is_set.load(Ordering::Acquire)
+            .then(|| self.offset.load(Ordering::Relaxed))
+    }
+    
+    /// Sets the offset.
+    pub fn set(&self, offset: i64) {
+        self.offset.store(offset, Ordering::Relaxed);
+        self.is_set.store(true, Ordering::Release);
+    }
+    
+    /// Requests the offset to be synced again. Does nothing if syncing is not enabled.
+    pub fn request_resync(&self) {
+        self.resync.notify_one();
+    }
+    
+    /// Waits until a resync is requested.
+    async fn resync_requested(&self) {
+        self.resync.notified().await;
+    }
+}
+
+/// Options for automatically syncing the time offset from Steam's servers.
+#[derive(Debug, Clone, Copy)]
+pub struct TimeSyncOptions {
+    /// How often to sync the offset. Default is 1 hour.
+    pub sync_interval: Duration,
+    /// The minimum duration between syncs. Rejected confirmation keys request a sync sooner than
+    /// [`TimeSyncOptions::sync_interval`], but no sooner than this after the last sync. Also used
+    /// as the delay before retrying a failed sync. Default is 1 minute.
+    pub min_resync_interval: Duration,
+}
+
+impl Default for TimeSyncOptions {
+    fn default() -> Self {
+        Self {
+            // unwrap is safe because the value is in range
+            sync_interval: Duration::try_seconds(DEFAULT_SYNC_INTERVAL_SECONDS).unwrap(),
+            // unwrap is safe because the value is in range
+            min_resync_interval: Duration::try_seconds(DEFAULT_MIN_RESYNC_INTERVAL_SECONDS)
+                .unwrap(),
+        }
+    }
+}
+
+/// Spawns the task that syncs the time offset. The task ends when cancelled.
+pub(crate) fn spawn_time_sync(
+    mobile_api: MobileAPI,
+    options: TimeSyncOptions,
+    cancellation_token: CancellationToken,
+) {
+    let sync_interval = options.sync_interval.to_std()
+        .unwrap_or(std::time::Duration::from_secs(DEFAULT_SYNC_INTERVAL_SECONDS as u64));
+    let min_resync_interval = options.min_resync_interval.to_std()
+        .unwrap_or(std::time::Duration::from_secs(DEFAULT_MIN_RESYNC_INTERVAL_SECONDS as u64))
+        // Prevents spamming requests.
+        .max(std::time::Duration::from_secs(1));
+    
+    tokio::spawn(async move {
+        loop {
+            let next_sync = match mobile_api.sync_time_offset().await {
+                Ok(offset) => {
+                    log::debug!("Synced time offset: {offset} seconds");
+                    sync_interval
+                },
+                Err(error) => {
+                    log::warn!("Failed to sync time offset: {error}");
+                    min_resync_interval
+                },
+            };
+            
+            tokio::select! {
+                // Ends the task.
+                _ = cancellation_token.cancelled() => return,
+                _ = async_std::task::sleep(next_sync) => continue,
+                _ = mobile_api.synced_time_offset.resync_requested() => {},
+            }
+            
+            // Waits before syncing again after a resync is requested.
+            tokio::select! {
+                _ = cancellation_token.cancelled() => return,
+                _ = async_std::task::sleep(min_resync_interval) => {},
+            }
+        }
+    });
+}
@@ -0,0 +1,51 @@
+use chrono::Duration;
+
+const DEFAULT_INITIAL_DELAY_SECONDS: i64 = 1;
+const DEFAULT_MAX_DELAY_SECONDS: i64 = 10;
+const DEFAULT_TIMEOUT_SECONDS: i64 = 60;
+
+/// Options for retrying confirming an offer until its confirmation appears.
+#[derive(Debug, Clone, Copy)]
+pub struct ConfirmRetryOptions {
+    /// The delay before checking confirmations again after the first attempt. The delay doubles
+    /// after each attempt. Default is 1 second.
+    pub initial_delay: Duration,
+    /// The maximum delay between attempts. Default is 10 seconds.
+    pub max_delay: Duration,
+    /// The duration to keep retrying for before giving up. Default is 60 seconds.
+    pub timeout: Duration,
+}
+
+impl Default for ConfirmRetryOptions {
+    fn default() -> Self {
+        Self {
+            // unwrap is safe because the value is in range
+            initial_delay: Duration::try_seconds(DEFAULT_INITIAL_DELAY_SECONDS).unwrap(),
+            // unwrap is safe because the value is in range
+            max_delay: Duration::try_seconds(DEFAULT_MAX_DELAY_SECONDS).unwrap(),
+            // unwrap is safe because the value is in range
+            timeout: Duration::try_seconds(DEFAULT_TIMEOUT_SECONDS).unwrap(),
+        }
+    }
+}
+
+impl ConfirmRetryOptions {
+    /// Gets the delay to use after `delay`.
+    pub(crate) fn next_delay(&self, delay: Duration) -> Duration {
+        (delay * 2).min(self.max_delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn delay_doubles_up_to_max() {
+        let options = ConfirmRetryOptions::default();
+        let delay = options.next_delay(options.initial_delay);
+        
+        assert_eq!(delay, Duration::try_seconds(2).unwrap());
+        assert_eq!(options.next_delay(Duration::try_seconds(8).unwrap()), options.max_delay);
+    }
+}
@@ -20,6 +20,9 @@ pub struct GetInventoryOptions<'a> {
     pub language: Language,
     /// The number of items to fetch per page. Defaults to 2000.
     pub page_size: u32,
+    /// The base URL for Steam community requests, including the scheme and optionally a port.
+    /// [`None`] uses `https://steamcommunity.com`.
+    pub community_url: Option<&'a str>,
 }
 
 impl Default for GetInventoryOptions<'_> {
@@ -32,6 +35,7 @@ impl Default for GetInventoryOptions<'_> {
             tradable_only: true,
             language: Language::English,
             page_size: 2000,
+            community_url: None,
         }
     }
 }
@@ -52,6 +56,7 @@ impl<'a> GetInventoryOptions<'a> {
             tradable_only: true,
             language: Language::English,
             page_size: 2000,
+            community_url: None,
         }
     }
 }
@@ -1,9 +1,11 @@
 //! Includes models used as parameters for making requests.
 
+mod confirm_retry;
 mod inventory;
 mod trade_history;
 mod trade_offer;
 
+pub use confirm_retry::ConfirmRetryOptions;
 pub use inventory::GetInventoryOptions;
 pub use trade_history::GetTradeHistoryOptions;
 pub use trade_offer::{NewTradeOffer, NewTradeOfferBuilder, NewTradeOfferItem};
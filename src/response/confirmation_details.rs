@@ -0,0 +1,160 @@
+use crate::SteamID;
+use crate::enums::ConfirmationType;
+use crate::error::ParseHtmlError;
+use crate::types::TradeOfferId;
+use lazy_regex::regex_captures;
+use scraper::{ElementRef, Html, Selector};
+use steamid_ng::{AccountType, Instance, InstanceFlags, InstanceType, Universe};
+
+/// Details for a mobile confirmation parsed from its details page. Use these to check that a
+/// confirmation matches what you expect before accepting it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ConfirmationDetails {
+    /// A confirmation for a trade offer.
+    Trade {
+        /// The ID of the trade offer.
+        tradeofferid: TradeOfferId,
+        /// The partner of the trade offer, if one could be found on the page.
+        partner: Option<SteamID>,
+    },
+    /// A confirmation for listing an item on the market.
+    MarketListing {
+        /// The name of the listed item, if one could be found on the page.
+        item_name: Option<String>,
+        /// The price of the listing as displayed on the page, e.g. "$2.09 ($2.40)". The first
+        /// amount is what you receive and the amount in parentheses is what the buyer pays.
+        price: Option<String>,
+    },
+    /// Any other type of confirmation. These details pages are not parsed.
+    Other,
+}
+
+impl ConfirmationDetails {
+    /// The ID of the trade offer if this is a confirmation for a trade offer.
+    pub fn tradeofferid(&self) -> Option<TradeOfferId> {
+        match self {
+            Self::Trade { tradeofferid, .. } => Some(*tradeofferid),
+            _ => None,
+        }
+    }
+    
+    /// Parses the details page for a confirmation of the given type.
+    pub(crate) fn parse(
+        html: &str,
+        confirmation_type: ConfirmationType,
+    ) -> Result<Self, ParseHtmlError> {
+        let document = Html::parse_document(html);
+        
+        match confirmation_type {
+            ConfirmationType::Trade => parse_trade(&document),
+            ConfirmationType::MarketSell => parse_market_listing(&document),
+            _ => Ok(Self::Other),
+        }
+    }
+}
+
+fn parse_trade(document: &Html) -> Result<ConfirmationDetails, ParseHtmlError> {
+    let tradeoffer_selector = Selector::parse(".tradeoffer")
+        .map_err(|_error| ParseHtmlError::ParseSelector)?;
+    let partner_selector = Selector::parse(".tradeoffer_partner [data-miniprofile]")
+        .map_err(|_error| ParseHtmlError::ParseSelector)?;
+    let tradeoffer_element = document.select(&tradeoffer_selector).next()
+        .ok_or(ParseHtmlError::Malformed("Missing trade offer element."))?;
+    let tradeofferid = tradeoffer_element.value().id()
+        .and_then(|id| regex_captures!(r#"^tradeofferid_(\d+)$"#, id))
+        .map(|(_, tradeofferid)| tradeofferid)
+        .ok_or(ParseHtmlError::Malformed("Missing trade offer ID."))?
+        .parse::<TradeOfferId>()?;
+    let partner = tradeoffer_element.select(&partner_selector).next()
+        .and_then(|element| element.value().attr("data-miniprofile"))
+        .and_then(|accountid| accountid.parse::<u32>().ok())
+        .map(|accountid| SteamID::new(
+            accountid,
+            Instance::new(InstanceType::Desktop, InstanceFlags::None),
+            AccountType::Individual,
+            Universe::Public
+        ));
+    
+    Ok(ConfirmationDetails::Trade {
+        tradeofferid,
+        partner,
+    })
+}
+
+fn parse_market_listing(document: &Html) -> Result<ConfirmationDetails, ParseHtmlError> {
+    let item_name_selector = Selector::parse("#confiteminfo_item_name")
+        .map_err(|_error| ParseHtmlError::ParseSelector)?;
+    let prices_selector = Selector::parse(".mobileconf_listing_prices")
+        .map_err(|_error| ParseHtmlError::ParseSelector)?;
+    let item_name = document.select(&item_name_selector).next()
+        .map(element_text)
+        .filter(|item_name| !item_name.is_empty());
+    let price = document.select(&prices_selector).next()
+        .map(element_text)
+        .map(|text| match text.split_once(':') {
+            // Removes the label e.g. "You receive:".
+            Some((_label, price)) => price.trim().to_string(),
+            None => text,
+        })
+        .filter(|price| !price.is_empty());
+    
+    if item_name.is_none() && price.is_none() {
+        return Err(ParseHtmlError::Malformed("Missing market listing details."));
+    }
+    
+    Ok(ConfirmationDetails::MarketListing {
+        item_name,
+        price,
+    })
+}
+
+/// Gets the text of an element with whitespace collapsed.
+fn element_text(element: ElementRef) -> String {
+    element.text()
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn parses_trade_details() {
+        let details = ConfirmationDetails::parse(
+            include_str!("fixtures/confirmation_details_trade.html"),
+            ConfirmationType::Trade,
+        ).unwrap();
+        
+        assert_eq!(details.tradeofferid(), Some(6437698405));
+        assert!(matches!(
+            details,
+            ConfirmationDetails::Trade { partner: Some(partner), .. }
+                if partner.account_id() == 119913840
+        ));
+    }
+    
+    #[test]
+    fn parses_market_listing_details() {
+        let details = ConfirmationDetails::parse(
+            include_str!("fixtures/confirmation_details_market.html"),
+            ConfirmationType::MarketSell,
+        ).unwrap();
+        
+        assert_eq!(details, ConfirmationDetails::MarketListing {
+            item_name: Some("Mann Co. Supply Crate Key".into()),
+            price: Some("$2.09 ($2.40)".into()),
+        });
+    }
+    
+    #[test]
+    fn missing_trade_offer_is_an_error() {
+        let details = ConfirmationDetails::parse(
+            include_str!("fixtures/confirmation_details_market.html"),
+            ConfirmationType::Trade,
+        );
+        
+        assert!(details.is_err());
+    }
+}
@@ -2,6 +2,7 @@
 mod asset;
 mod classinfo;
 mod confirmation;
+mod confirmation_details;
 mod currency;
 mod trade;
 mod trade_offer;
@@ -12,6 +13,7 @@ mod user_details;
 pub use asset::{Asset, AssetProperty, AssetPropertyValue};
 pub use classinfo::{Action, ClassInfo, Description, Tag};
 pub use confirmation::Confirmation;
+pub use confirmation_details::ConfirmationDetails;
 pub use currency::Currency;
 pub use trade::{Trade, TradeAsset, Trades};
 pub use trade_offer::TradeOffer;
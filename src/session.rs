@@ -1,3 +1,7 @@
+use crate::SteamID;
+use crate::jwt::decode_jwt;
+use crate::time::{self, ServerTime};
+use chrono::Duration;
 
 /// Session data from cookies.
 #[derive(Debug, Clone, Default)]
@@ -8,4 +12,125 @@ pub struct Session {
     pub access_token: String,
     /// The Steam ID of the user.
     pub steamid: u64,
+    /// When the access token expires. [`None`] if the token could not be decoded.
+    pub expires_at: Option<ServerTime>,
+    /// When the access token was issued.
+    pub issued_at: Option<ServerTime>,
+    /// The audiences the access token is valid for.
+    pub audience: Vec<String>,
+    /// The Steam ID the access token was issued for.
+    pub subject: Option<u64>,
+}
+
+impl Session {
+    /// Creates a new [`Session`], decoding the claims of the access token.
+    pub fn new(
+        sessionid: String,
+        access_token: String,
+        steamid: u64,
+    ) -> Self {
+        let claims = decode_jwt(&access_token);
+        let expires_at = claims.as_ref()
+            .map(|claims| time::timestamp_to_server_time(claims.exp));
+        let issued_at = claims.as_ref()
+            .and_then(|claims| claims.iat)
+            .map(time::timestamp_to_server_time);
+        let subject = claims.as_ref()
+            .and_then(|claims| claims.sub.as_ref())
+            .and_then(|sub| sub.parse().ok());
+        let audience = claims
+            .map(|claims| claims.aud)
+            .unwrap_or_default();
+        
+        Self {
+            sessionid,
+            access_token,
+            steamid,
+            expires_at,
+            issued_at,
+            audience,
+            subject,
+        }
+    }
+    
+    /// Gets the public information about this session. [`None`] if the Steam ID is not valid.
+    pub fn info(&self) -> Option<SessionInfo> {
+        Some(SessionInfo {
+            steamid: SteamID::try_from(self.steamid).ok()?,
+            expires_at: self.expires_at,
+            issued_at: self.issued_at,
+            audience: self.audience.clone(),
+            subject: self.subject.and_then(|subject| SteamID::try_from(subject).ok()),
+        })
+    }
+}
+
+/// Information about the current session, decoded from the access token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+    /// The Steam ID the session belongs to.
+    pub steamid: SteamID,
+    /// When the access token expires. [`None`] if the token could not be decoded.
+    pub expires_at: Option<ServerTime>,
+    /// When the access token was issued.
+    pub issued_at: Option<ServerTime>,
+    /// The audiences the access token is valid for, e.g. `web:community`.
+    pub audience: Vec<String>,
+    /// The Steam ID the access token was issued for, from its `sub` claim. [`None`] if the token
+    /// could not be decoded.
+    pub subject: Option<SteamID>,
+}
+
+impl SessionInfo {
+    /// The duration until the access token expires. Negative if it has already expired.
+    /// [`None`] if the expiry is unknown.
+    pub fn time_until_expiry(&self) -> Option<Duration> {
+        self.expires_at.map(|expires_at| expires_at - time::get_server_time_now())
+    }
+    
+    /// Whether the access token has expired. Returns `false` if the expiry is unknown.
+    pub fn is_expired(&self) -> bool {
+        self.time_until_expiry().is_some_and(|duration| duration <= Duration::zero())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jwt::tests::encode_jwt;
+    
+    fn access_token(exp: i64) -> String {
+        encode_jwt(&format!(
+            r#"{{"sub":"76561198000000000","exp":{exp},"iat":1699900000,"aud":["web:community"]}}"#,
+        ))
+    }
+    
+    #[test]
+    fn decodes_session_info() {
+        let exp = time::get_server_time_now().timestamp() + 600;
+        let session = Session::new("sessionid".into(), access_token(exp), 76561198000000000);
+        let info = session.info().unwrap();
+        
+        assert_eq!(u64::from(info.steamid), 76561198000000000);
+        assert_eq!(info.expires_at.map(|date| date.timestamp()), Some(exp));
+        assert_eq!(info.issued_at.map(|date| date.timestamp()), Some(1699900000));
+        assert_eq!(info.audience, vec!["web:community"]);
+        assert_eq!(info.subject, Some(info.steamid));
+        assert!(!info.is_expired());
+        
+        let session = Session::new("sessionid".into(), access_token(1700000000), 76561198000000000);
+        
+        assert!(session.info().unwrap().is_expired());
+    }
+    
+    #[test]
+    fn unknown_expiry_is_not_expired() {
+        let info = Session::new("sessionid".into(), "token".into(), 76561198000000000)
+            .info()
+            .unwrap();
+        
+        assert!(info.expires_at.is_none());
+        assert!(info.subject.is_none());
+        assert!(!info.is_expired());
+    }
 }
@@ -0,0 +1,193 @@
+//! Persisting sessions to disk so they survive restarts.
+
+use crate::SteamID;
+use crate::error::{FileError, StoreError};
+use crate::helpers::write_file_atomic;
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use aes_gcm::{Aes256Gcm, Key, KeyInit, Nonce};
+use aes_gcm::aead::Aead;
+use serde::{Deserialize, Serialize};
+
+/// The length of the nonce prepended to encrypted files.
+const NONCE_LENGTH: usize = 12;
+
+/// A session saved by a [`SessionStore`].
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StoredSession {
+    /// The SteamID of the account.
+    pub steamid: SteamID,
+    /// The cookies the session was set with.
+    pub cookies: Vec<String>,
+    /// The refresh token used to renew the session.
+    pub refresh_token: Option<String>,
+}
+
+impl fmt::Debug for StoredSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StoredSession")
+            .field("steamid", &self.steamid)
+            .field("cookies", &"<redacted>")
+            .field("refresh_token", &self.refresh_token.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+/// Stores sessions as encrypted files in a directory. Each account is saved to
+/// `session_<steamid>.bin`, encrypted using AES-256-GCM with the provided key.
+///
+/// Sessions are loaded synchronously since they are small and are loaded when building the
+/// manager. Saving is asynchronous since sessions are saved while renewing them.
+#[derive(Clone)]
+pub struct SessionStore {
+    /// The directory to save session files to.
+    directory: PathBuf,
+    cipher: Aes256Gcm,
+}
+
+impl fmt::Debug for SessionStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionStore")
+            .field("directory", &self.directory)
+            .field("cipher", &"<redacted>")
+            .finish()
+    }
+}
+
+impl SessionStore {
+    /// Creates a new [`SessionStore`] which saves files to `directory` encrypted with `key`.
+    /// Use the same key to load sessions that were saved previously.
+    pub fn new<T>(
+        directory: T,
+        key: [u8; 32],
+    ) -> Self
+    where
+        T: Into<PathBuf>,
+    {
+        Self {
+            directory: directory.into(),
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+        }
+    }
+    
+    /// The directory session files are saved to.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+    
+    /// Loads the session for an account. [`None`] if nothing has been saved for the account.
+    ///
+    /// # Errors
+    /// - If the file could not be read.
+    /// - If the file could not be decrypted, e.g. when the key does not match.
+    pub fn load(
+        &self,
+        steamid: SteamID,
+    ) -> Result<Option<StoredSession>, StoreError> {
+        let bytes = match std::fs::read(self.filepath(steamid)) {
+            Ok(bytes) => bytes,
+            // Nothing has been saved for this account yet.
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(FileError::FileSystem(error).into()),
+        };
+        
+        if bytes.len() < NONCE_LENGTH {
+            return Err(StoreError::Encryption);
+        }
+        
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LENGTH);
+        let data = self.cipher.decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| StoreError::Encryption)?;
+        let session = serde_json::from_slice(&data)?;
+        
+        Ok(Some(session))
+    }
+    
+    /// Saves the session for an account, replacing any previously saved session.
+    ///
+    /// # Errors
+    /// - If the file could not be written.
+    pub async fn save(
+        &self,
+        session: &StoredSession,
+    ) -> Result<(), StoreError> {
+        let data = serde_json::to_vec(session)?;
+        let nonce = rand::random::<[u8; NONCE_LENGTH]>();
+        let ciphertext = self.cipher.encrypt(Nonce::from_slice(&nonce), data.as_slice())
+            .map_err(|_| StoreError::Encryption)?;
+        let mut bytes = nonce.to_vec();
+        
+        bytes.extend(ciphertext);
+        async_fs::create_dir_all(&self.directory).await
+            .map_err(FileError::FileSystem)?;
+        write_file_atomic(self.filepath(session.steamid), &bytes).await
+            .map_err(FileError::FileSystem)?;
+        
+        Ok(())
+    }
+    
+    /// Removes the saved session for an account.
+    ///
+    /// # Errors
+    /// - If the file exists but could not be removed.
+    pub fn remove(
+        &self,
+        steamid: SteamID,
+    ) -> Result<(), StoreError> {
+        match std::fs::remove_file(self.filepath(steamid)) {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(FileError::FileSystem(error).into()),
+        }
+    }
+    
+    fn filepath(&self, steamid: SteamID) -> PathBuf {
+        self.directory.join(format!("session_{}.bin", u64::from(steamid)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn stored_session() -> StoredSession {
+        StoredSession {
+            steamid: SteamID::try_from(76561198000000000).unwrap(),
+            cookies: vec!["steamLoginSecure=76561198000000000%7C%7Ctoken".into()],
+            refresh_token: Some("refresh".into()),
+        }
+    }
+    
+    #[tokio::test]
+    async fn saves_and_loads_encrypted_sessions() {
+        let directory = std::env::temp_dir()
+            .join(format!("session_store_test_{}", rand::random::<u64>()));
+        let store = SessionStore::new(&directory, [1; 32]);
+        let session = stored_session();
+        
+        assert!(store.load(session.steamid).unwrap().is_none());
+        
+        store.save(&session).await.unwrap();
+        
+        let bytes = std::fs::read(store.filepath(session.steamid)).unwrap();
+        
+        // The contents are not stored in plain text.
+        assert!(!String::from_utf8_lossy(&bytes).contains("steamLoginSecure"));
+        
+        let loaded = store.load(session.steamid).unwrap().unwrap();
+        
+        assert_eq!(loaded.cookies, session.cookies);
+        assert_eq!(loaded.refresh_token, session.refresh_token);
+        
+        let wrong_key = SessionStore::new(&directory, [2; 32]);
+        
+        assert!(matches!(wrong_key.load(session.steamid), Err(StoreError::Encryption)));
+        
+        store.remove(session.steamid).unwrap();
+        
+        assert!(store.load(session.steamid).unwrap().is_none());
+        
+        std::fs::remove_dir_all(&directory).ok();
+    }
+}
@@ -5,7 +5,7 @@ use crate::response::{Asset, AssetProperty, ClassInfo};
 use crate::request::GetInventoryOptions;
 use crate::types::*;
 use crate::helpers::{parses_response, extract_auth_data_from_cookies};
-use crate::helpers::{COMMUNITY_HOSTNAME, CookiesData};
+use crate::helpers::{COMMUNITY_HOSTNAME, DEFAULT_COMMUNITY_URL, CookiesData};
 use crate::error::{Error, ParseHtmlError, MissingClassInfoError};
 use crate::serialize;
 use std::collections::HashMap;
@@ -59,8 +59,11 @@ pub async fn get_inventory<'a>(
     let steamid_64 = u64::from(options.steamid);
     let appid = options.appid;
     let contextid = options.contextid;
-    let uri = format!("https://{COMMUNITY_HOSTNAME}/inventory/{steamid_64}/{appid}/{contextid}");
-    let referer = format!("https://{COMMUNITY_HOSTNAME}/profiles/{steamid_64}/inventory");
+    let community_url = options.community_url
+        .unwrap_or(DEFAULT_COMMUNITY_URL)
+        .trim_end_matches('/');
+    let uri = format!("{community_url}/inventory/{steamid_64}/{appid}/{contextid}");
+    let referer = format!("{community_url}/profiles/{steamid_64}/inventory");
     
     loop {
         let response = options.client.get(&uri)
@@ -0,0 +1,279 @@
+//! Routes for logging in through `IAuthenticationService` and finalizing the login into community
+//! cookies.
+
+use super::routes::{lock, SharedState};
+use crate::jwt::decode_jwt;
+use std::collections::HashMap;
+use another_steam_totp::generate_auth_code;
+use axum::Router;
+use axum::extract::{Form, Multipart, State};
+use axum::http::HeaderMap;
+use axum::http::header::{HOST, SET_COOKIE};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::Json;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use rsa::Pkcs1v15Encrypt;
+use rsa::traits::PublicKeyParts;
+use serde::Deserialize;
+use serde_json::json;
+
+/// The timestamp returned with the RSA key. Steam uses this to identify the key.
+const RSA_TIMESTAMP: u64 = 1700000000000000;
+/// How long refresh tokens issued by the server are valid for.
+const REFRESH_TOKEN_LIFETIME_SECONDS: i64 = 200 * 24 * 60 * 60;
+/// The number of seconds before and after the current time Steam Guard codes are accepted from.
+const STEAM_GUARD_CODE_LEEWAY_SECONDS: i64 = 30;
+
+/// EResult for an invalid account name or password.
+const ERESULT_INVALID_PASSWORD: u32 = 5;
+/// EResult for an invalid parameter, e.g. an auth session that does not exist.
+const ERESULT_INVALID_PARAM: u32 = 8;
+/// EResult for a Steam Guard code that was already accepted.
+const ERESULT_DUPLICATE_REQUEST: u32 = 29;
+/// EResult for a Steam Guard code that does not match.
+const ERESULT_TWO_FACTOR_CODE_MISMATCH: u32 = 88;
+
+/// No Steam Guard confirmation is required.
+const CONFIRMATION_TYPE_NONE: u32 = 1;
+/// A Steam Guard code from the mobile authenticator.
+const CONFIRMATION_TYPE_DEVICE_CODE: u32 = 3;
+
+/// Creates the router for the login endpoints.
+pub(super) fn router() -> Router<SharedState> {
+    Router::new()
+        .route(
+            "/IAuthenticationService/GetPasswordRSAPublicKey/v1/",
+            get(get_password_rsa_public_key),
+        )
+        .route(
+            "/IAuthenticationService/BeginAuthSessionViaCredentials/v1/",
+            post(begin_auth_session_via_credentials),
+        )
+        .route(
+            "/IAuthenticationService/UpdateAuthSessionWithSteamGuardCode/v1/",
+            post(update_auth_session_with_steam_guard_code),
+        )
+        .route(
+            "/IAuthenticationService/PollAuthSessionStatus/v1/",
+            post(poll_auth_session_status),
+        )
+        .route("/jwt/finalizelogin", post(finalize_login))
+        .route("/login/settoken", post(set_token))
+}
+
+/// A Web API response with an EResult other than OK. Steam sends the EResult in a header.
+fn eresult(code: u32) -> Response {
+    (
+        [("x-eresult", code.to_string())],
+        Json(json!({ "response": {} })),
+    ).into_response()
+}
+
+/// Whether `token` is a refresh token. Tokens are not signed, so any account is accepted.
+fn is_refresh_token(
+    token: &str,
+) -> bool {
+    decode_jwt(token).is_some_and(|claims| claims.aud.iter().any(|audience| audience == "renew"))
+}
+
+/// Reads the text fields of a multipart form.
+async fn read_multipart(
+    mut multipart: Multipart,
+) -> HashMap<String, String> {
+    let mut fields = HashMap::new();
+    
+    while let Ok(Some(field)) = multipart.next_field().await {
+        let Some(name) = field.name().map(String::from) else {
+            continue;
+        };
+        
+        if let Ok(value) = field.text().await {
+            fields.insert(name, value);
+        }
+    }
+    
+    fields
+}
+
+async fn get_password_rsa_public_key(
+    State(state): State<SharedState>,
+) -> Response {
+    let mut state = lock(&state);
+    let public_key = state.rsa_key().to_public_key();
+    
+    Json(json!({
+        "response": {
+            "publickey_mod": public_key.n().to_str_radix(16),
+            "publickey_exp": public_key.e().to_str_radix(16),
+            "timestamp": RSA_TIMESTAMP.to_string(),
+        },
+    })).into_response()
+}
+
+#[derive(Deserialize)]
+struct BeginAuthSessionForm {
+    account_name: String,
+    encrypted_password: String,
+}
+
+async fn begin_auth_session_via_credentials(
+    State(state): State<SharedState>,
+    Form(form): Form<BeginAuthSessionForm>,
+) -> Response {
+    let mut state = lock(&state);
+    let password = STANDARD.decode(&form.encrypted_password).ok()
+        .and_then(|encrypted| state.rsa_key().decrypt(Pkcs1v15Encrypt, &encrypted).ok());
+    
+    let is_valid = form.account_name == state.account_name &&
+        password.as_deref() == Some(state.password.as_bytes());
+    
+    if !is_valid {
+        return eresult(ERESULT_INVALID_PASSWORD);
+    }
+    
+    let client_id = state.next_id();
+    let needs_code = state.shared_secret.is_some();
+    let confirmation_type = if needs_code {
+        CONFIRMATION_TYPE_DEVICE_CODE
+    } else {
+        CONFIRMATION_TYPE_NONE
+    };
+    
+    state.auth_sessions.insert(client_id, !needs_code);
+    Json(json!({
+        "response": {
+            "client_id": client_id.to_string(),
+            "request_id": format!("request{client_id}"),
+            "interval": 0.1,
+            "allowed_confirmations": [{ "confirmation_type": confirmation_type }],
+            "steamid": u64::from(state.steamid).to_string(),
+        },
+    })).into_response()
+}
+
+#[derive(Deserialize)]
+struct SteamGuardCodeForm {
+    client_id: u64,
+    code: String,
+}
+
+async fn update_auth_session_with_steam_guard_code(
+    State(state): State<SharedState>,
+    Form(form): Form<SteamGuardCodeForm>,
+) -> Response {
+    let mut state = lock(&state);
+    let is_valid_code = state.shared_secret
+        .as_ref()
+        .is_some_and(|shared_secret| {
+            [-STEAM_GUARD_CODE_LEEWAY_SECONDS, 0, STEAM_GUARD_CODE_LEEWAY_SECONDS]
+                .into_iter()
+                .filter_map(|offset| generate_auth_code(shared_secret, Some(offset)).ok())
+                .any(|code| code == form.code)
+        });
+    let Some(approved) = state.auth_sessions.get_mut(&form.client_id) else {
+        return eresult(ERESULT_INVALID_PARAM);
+    };
+    
+    if *approved {
+        return eresult(ERESULT_DUPLICATE_REQUEST);
+    }
+    
+    if !is_valid_code {
+        return eresult(ERESULT_TWO_FACTOR_CODE_MISMATCH);
+    }
+    
+    *approved = true;
+    Json(json!({ "response": {} })).into_response()
+}
+
+#[derive(Deserialize)]
+struct PollAuthSessionForm {
+    client_id: u64,
+}
+
+async fn poll_auth_session_status(
+    State(state): State<SharedState>,
+    Form(form): Form<PollAuthSessionForm>,
+) -> Response {
+    let mut state = lock(&state);
+    
+    match state.auth_sessions.get(&form.client_id) {
+        Some(true) => {
+            state.auth_sessions.remove(&form.client_id);
+            
+            let steamid = state.steamid;
+            
+            Json(json!({
+                "response": {
+                    "refresh_token": super::create_token(
+                        steamid,
+                        &["web", "renew", "derive"],
+                        REFRESH_TOKEN_LIFETIME_SECONDS,
+                    ),
+                    "access_token": super::create_token(
+                        steamid,
+                        &["web"],
+                        super::ACCESS_TOKEN_LIFETIME_SECONDS,
+                    ),
+                    "account_name": state.account_name,
+                },
+            })).into_response()
+        },
+        // Not yet approved.
+        Some(false) => Json(json!({ "response": {} })).into_response(),
+        None => eresult(ERESULT_INVALID_PARAM),
+    }
+}
+
+async fn finalize_login(
+    State(state): State<SharedState>,
+    headers: HeaderMap,
+    multipart: Multipart,
+) -> Response {
+    let fields = read_multipart(multipart).await;
+    let state = lock(&state);
+    let Some(nonce) = fields.get("nonce")
+        .filter(|nonce| is_refresh_token(nonce)) else {
+        return Json(json!({ "error": ERESULT_INVALID_PARAM })).into_response();
+    };
+    let host = headers.get(HOST)
+        .and_then(|host| host.to_str().ok())
+        .unwrap_or_default();
+    
+    Json(json!({
+        "steamID": u64::from(state.steamid).to_string(),
+        "transfer_info": [
+            {
+                "url": "https://store.steampowered.com/login/settoken",
+                "params": { "nonce": nonce, "auth": "mockauth" },
+            },
+            {
+                "url": format!("http://{host}/login/settoken"),
+                "params": { "nonce": nonce, "auth": "mockauth" },
+            },
+        ],
+    })).into_response()
+}
+
+/// Sets the cookies for a new session. The server is logged in afterwards.
+async fn set_token(
+    State(state): State<SharedState>,
+    multipart: Multipart,
+) -> Response {
+    let fields = read_multipart(multipart).await;
+    let mut state = lock(&state);
+    let is_valid = fields.get("nonce").is_some_and(|nonce| is_refresh_token(nonce)) &&
+        fields.get("steamID") == Some(&u64::from(state.steamid).to_string());
+    
+    if !is_valid {
+        return Json(json!({ "result": ERESULT_INVALID_PARAM })).into_response();
+    }
+    
+    state.logged_in = true;
+    
+    let cookie = format!("{}; Path=/; HttpOnly", super::login_secure_cookie(state.steamid));
+    
+    ([(SET_COOKIE, cookie)], Json(json!({ "result": 1 }))).into_response()
+}
@@ -0,0 +1,470 @@
+//! A mock Steam server for testing without a Steam account. Requires the `test-util` feature.
+//!
+//! [`MockSteamServer`] runs in-process and serves the Web API and community endpoints used for
+//! trade offers, inventories, trade history and mobile confirmations. Point the manager at it
+//! using [`TradeOfferManagerBuilder::community_url`](crate::TradeOfferManagerBuilder::community_url)
+//! and [`TradeOfferManagerBuilder::api_url`](crate::TradeOfferManagerBuilder::api_url). Responses
+//! are built from a [`MockSteamState`] which can be changed at any time to script a scenario,
+//! e.g. our partner accepting an offer between polls. Logging in using
+//! [`AuthClient`](crate::auth::AuthClient) is also supported with the account name and password
+//! from the state.
+//!
+//! # Examples
+//! ```no_run
+//! use steam_tradeoffer_manager::{SteamID, TradeOfferManager};
+//! use steam_tradeoffer_manager::enums::TradeOfferState;
+//! use steam_tradeoffer_manager::test_util::MockSteamServer;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let steamid = SteamID::try_from(76561198000000001).unwrap();
+//!     let partner = SteamID::try_from(76561198000000002).unwrap();
+//!     let server = MockSteamServer::start(steamid).await.unwrap();
+//!     let manager = TradeOfferManager::builder()
+//!         .community_url(server.url())
+//!         .api_url(server.url())
+//!         .cookies(server.cookies())
+//!         .build();
+//!     let tradeofferid = server.state().add_received_offer(partner, Vec::new(), Vec::new());
+//!     let mut offers = manager.get_active_trade_offers().await.unwrap();
+//!
+//!     manager.decline_offer(&mut offers[0]).await.unwrap();
+//!
+//!     assert_eq!(
+//!         server.state().offers[&tradeofferid].trade_offer_state,
+//!         TradeOfferState::Declined,
+//!     );
+//! }
+//! ```
+
+mod auth;
+mod routes;
+mod state;
+
+pub use state::MockSteamState;
+
+use crate::SteamID;
+use crate::time;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use tokio_util::sync::{CancellationToken, DropGuard};
+
+/// How long access tokens issued by the server are valid for.
+const ACCESS_TOKEN_LIFETIME_SECONDS: i64 = 24 * 60 * 60;
+
+/// A mock Steam server listening on a random local port. The server stops when dropped.
+#[derive(Debug)]
+pub struct MockSteamServer {
+    address: SocketAddr,
+    state: Arc<Mutex<MockSteamState>>,
+    _shutdown: DropGuard,
+}
+
+impl MockSteamServer {
+    /// Starts a server acting for the account `steamid`. Must be called within a tokio runtime.
+    ///
+    /// # Errors
+    /// - If a local port could not be bound.
+    pub async fn start(
+        steamid: SteamID,
+    ) -> std::io::Result<Self> {
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0)).await?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MockSteamState::new(steamid)));
+        let router = routes::router(Arc::clone(&state));
+        let cancellation_token = CancellationToken::new();
+        let shutdown = cancellation_token.clone();
+        
+        tokio::spawn(async move {
+            let result = axum::serve(listener, router)
+                .with_graceful_shutdown(async move { shutdown.cancelled().await })
+                .await;
+            
+            if let Err(error) = result {
+                log::error!("Mock Steam server stopped: {error}");
+            }
+        });
+        
+        Ok(Self {
+            address,
+            state,
+            _shutdown: cancellation_token.drop_guard(),
+        })
+    }
+    
+    /// The base URL of the server, e.g. `http://127.0.0.1:8080`. Used for both the community and
+    /// Web API URLs.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+    
+    /// Locks the state of the server. Do not hold the lock while awaiting requests to the server.
+    pub fn state(&self) -> MutexGuard<'_, MockSteamState> {
+        // The state is still usable if a test panicked while holding the lock.
+        self.state.lock().unwrap_or_else(|error| error.into_inner())
+    }
+    
+    /// Cookies for a session with the server. The access token expires after a day.
+    pub fn cookies(&self) -> Vec<String> {
+        let steamid = self.state().steamid;
+        
+        vec![
+            "sessionid=mocksessionid".into(),
+            login_secure_cookie(steamid),
+        ]
+    }
+}
+
+/// Creates an unsigned token for `steamid` in the format Steam uses.
+pub(crate) fn create_token(
+    steamid: SteamID,
+    audience: &[&str],
+    lifetime_seconds: i64,
+) -> String {
+    let exp = time::get_server_time_now().timestamp() + lifetime_seconds;
+    let header = URL_SAFE_NO_PAD.encode(r#"{"typ":"JWT","alg":"EdDSA"}"#);
+    let payload = URL_SAFE_NO_PAD.encode(serde_json::json!({
+        "sub": u64::from(steamid).to_string(),
+        "aud": audience,
+        "exp": exp,
+    }).to_string());
+    
+    format!("{header}.{payload}.signature")
+}
+
+/// Creates the `steamLoginSecure` cookie for a new session with `steamid`.
+pub(crate) fn login_secure_cookie(steamid: SteamID) -> String {
+    let access_token = create_token(steamid, &["web:community"], ACCESS_TOKEN_LIFETIME_SECONDS);
+    
+    format!("steamLoginSecure={}%7C%7C{access_token}", u64::from(steamid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TradeOfferManager;
+    use crate::api::response::RawAsset;
+    use crate::auth::{AuthClient, Credentials};
+    use crate::enums::{ConfirmOutcome, TradeOfferState};
+    use crate::polling::{MemoryPollDataStore, PollAction, PollEvent, PollOptions, PollType};
+    use crate::request::{ConfirmRetryOptions, GetTradeHistoryOptions, NewTradeOffer};
+    use crate::response::ClassInfo;
+    use crate::error::{AuthError, Error};
+    
+    const STEAMID: u64 = 76561198000000001;
+    const PARTNER: u64 = 76561198000000002;
+    const SHARED_SECRET: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+    
+    fn auth_client(server: &MockSteamServer) -> AuthClient {
+        AuthClient::builder()
+            .api_url(server.url())
+            .login_url(server.url())
+            .community_url(server.url())
+            .build()
+    }
+    
+    fn asset(assetid: u64) -> RawAsset {
+        RawAsset {
+            appid: 440,
+            contextid: 2,
+            assetid,
+            amount: 1,
+            missing: false,
+            classid: 101785959,
+            instanceid: Some(11040578),
+        }
+    }
+    
+    #[tokio::test]
+    async fn runs_offer_lifecycle() {
+        let steamid = SteamID::try_from(STEAMID).unwrap();
+        let partner = SteamID::try_from(PARTNER).unwrap();
+        let server = MockSteamServer::start(steamid).await.unwrap();
+        let directory = std::env::temp_dir()
+            .join(format!("mock_steam_test_{}", rand::random::<u64>()));
+        
+        {
+            let mut state = server.state();
+            
+            state.add_classinfo(ClassInfo {
+                classid: 101785959,
+                instanceid: Some(11040578),
+                name: "Mann Co. Supply Crate Key".into(),
+                tradable: true,
+                ..Default::default()
+            });
+            state.add_asset(steamid, asset(1));
+            state.add_asset(partner, asset(2));
+        }
+        
+        let manager = TradeOfferManager::builder()
+            .community_url(server.url())
+            .api_url(server.url())
+            .cookies(server.cookies())
+            .identity_secret("AAAAAAAAAAAAAAAAAAAAAAAAAAA=".into())
+            .data_directory(&directory)
+            .poll_data_store(MemoryPollDataStore::new())
+            .build();
+        let inventory = manager.get_my_inventory(440, 2, true).await.unwrap();
+        
+        assert_eq!(inventory.len(), 1);
+        assert_eq!(inventory[0].classinfo.name, "Mann Co. Supply Crate Key");
+        
+        let offer = NewTradeOffer::builder(partner)
+            .items_to_give(inventory)
+            .items_to_receive(vec![asset(2)])
+            .build();
+        let sent_offer = manager.send_offer(&offer).await.unwrap();
+        
+        assert!(sent_offer.needs_mobile_confirmation);
+        
+        let (sender, mut receiver) = manager.start_polling(PollOptions::default()).unwrap();
+        let events = receiver.recv().await.unwrap().unwrap();
+        
+        assert!(matches!(
+            &events[..],
+            [PollEvent::NewSentOffer(offer)] if offer.tradeofferid == sent_offer.tradeofferid,
+        ));
+        
+        manager.confirm_offer_id(sent_offer.tradeofferid).await.unwrap();
+        server.state().set_offer_state(sent_offer.tradeofferid, TradeOfferState::Accepted);
+        sender.send(PollAction::DoPoll(PollType::Auto)).await.unwrap();
+        
+        let events = receiver.recv().await.unwrap().unwrap();
+        
+        assert!(matches!(
+            &events[..],
+            [PollEvent::OfferAccepted { from: TradeOfferState::CreatedNeedsConfirmation, .. }],
+        ));
+        
+        let trades = manager.get_trade_history(&GetTradeHistoryOptions {
+            max_trades: 100,
+            ..Default::default()
+        }).await.unwrap();
+        
+        assert_eq!(trades.trades.len(), 1);
+        
+        let tradeofferid = server.state().add_received_offer(partner, Vec::new(), vec![asset(2)]);
+        let mut offers = manager.get_active_trade_offers().await.unwrap();
+        
+        assert_eq!(offers.len(), 1);
+        
+        manager.decline_offer(&mut offers[0]).await.unwrap();
+        
+        assert_eq!(
+            server.state().offers[&tradeofferid].trade_offer_state,
+            TradeOfferState::Declined,
+        );
+        
+        server.state().logged_in = false;
+        
+        assert!(matches!(
+            manager.get_trade_confirmations().await,
+            Err(crate::error::Error::Confirmation(_)),
+        ));
+        assert!(matches!(
+            manager.get_confirmation_details(&crate::fixtures::confirmation(1, 1)).await,
+            Err(crate::error::Error::NotLoggedIn),
+        ));
+        
+        std::fs::remove_dir_all(&directory).ok();
+    }
+    
+    #[tokio::test]
+    async fn logs_in_with_steam_guard_code() {
+        let steamid = SteamID::try_from(STEAMID).unwrap();
+        let server = MockSteamServer::start(steamid).await.unwrap();
+        
+        {
+            let mut state = server.state();
+            
+            state.shared_secret = Some(SHARED_SECRET.into());
+            state.logged_in = false;
+        }
+        
+        let credentials = Credentials::new("mockuser", "mockpassword")
+            .shared_secret(SHARED_SECRET);
+        let session = auth_client(&server).login(&credentials).await.unwrap();
+        
+        assert_eq!(session.steamid, steamid);
+        assert_eq!(session.account_name, "mockuser");
+        assert!(session.cookies.iter().any(|cookie| cookie.starts_with("steamLoginSecure=")));
+        assert!(session.cookies.iter().any(|cookie| cookie.starts_with("sessionid=")));
+        assert!(server.state().logged_in);
+        
+        let manager = TradeOfferManager::builder()
+            .community_url(server.url())
+            .api_url(server.url())
+            .cookies(session.cookies)
+            .build();
+        
+        assert!(manager.check_session().await.unwrap());
+    }
+    
+    #[tokio::test]
+    async fn rejects_invalid_login() {
+        let steamid = SteamID::try_from(STEAMID).unwrap();
+        let server = MockSteamServer::start(steamid).await.unwrap();
+        let auth = auth_client(&server);
+        
+        server.state().shared_secret = Some(SHARED_SECRET.into());
+        
+        assert!(matches!(
+            auth.login(&Credentials::new("mockuser", "wrong").shared_secret(SHARED_SECRET)).await,
+            Err(Error::Auth(AuthError::InvalidPassword)),
+        ));
+        assert!(matches!(
+            auth.login(&Credentials::new("mockuser", "mockpassword")).await,
+            Err(Error::Auth(AuthError::SteamGuardCodeRequired)),
+        ));
+        assert!(matches!(
+            auth.login(&Credentials::new("mockuser", "mockpassword")
+                .shared_secret("BBBBBBBBBBBBBBBBBBBBBBBBBBE=")).await,
+            Err(Error::Auth(AuthError::InvalidSteamGuardCode)),
+        ));
+    }
+    
+    #[tokio::test]
+    async fn renews_session_using_refresh_token() {
+        let steamid = SteamID::try_from(STEAMID).unwrap();
+        let server = MockSteamServer::start(steamid).await.unwrap();
+        let session = auth_client(&server)
+            .login(&Credentials::new("mockuser", "mockpassword"))
+            .await
+            .unwrap();
+        let directory = std::env::temp_dir()
+            .join(format!("mock_steam_test_{}", rand::random::<u64>()));
+        let manager = TradeOfferManager::builder()
+            .community_url(server.url())
+            .api_url(server.url())
+            .login_url(server.url())
+            .cookies(session.cookies)
+            .refresh_token(session.refresh_token)
+            .identity_secret("AAAAAAAAAAAAAAAAAAAAAAAAAAA=".into())
+            .data_directory(&directory)
+            .build();
+        
+        server.state().logged_in = false;
+        
+        // The session is renewed and the request is retried.
+        manager.get_trade_confirmations().await.unwrap();
+        
+        assert!(server.state().logged_in);
+        assert!(manager.check_session().await.unwrap());
+        
+        std::fs::remove_dir_all(&directory).ok();
+    }
+    
+    #[tokio::test]
+    async fn cancels_offers_after_duration_set_when_sending() {
+        let steamid = SteamID::try_from(STEAMID).unwrap();
+        let partner = SteamID::try_from(PARTNER).unwrap();
+        let server = MockSteamServer::start(steamid).await.unwrap();
+        let directory = std::env::temp_dir()
+            .join(format!("mock_steam_test_{}", rand::random::<u64>()));
+        
+        {
+            let mut state = server.state();
+            
+            state.require_confirmation = false;
+            state.add_classinfo(ClassInfo {
+                classid: 101785959,
+                instanceid: Some(11040578),
+                name: "Mann Co. Supply Crate Key".into(),
+                tradable: true,
+                ..Default::default()
+            });
+            state.add_asset(steamid, asset(1));
+            state.add_asset(steamid, asset(3));
+        }
+        
+        let manager = TradeOfferManager::builder()
+            .community_url(server.url())
+            .api_url(server.url())
+            .cookies(server.cookies())
+            .data_directory(&directory)
+            .poll_data_store(MemoryPollDataStore::new())
+            .build();
+        let offer = |assetid| NewTradeOffer::builder(partner)
+            .items_to_give(vec![asset(assetid)])
+            .build();
+        let is_cancelled = |events: &[PollEvent], tradeofferid| events
+            .iter()
+            .any(|event| matches!(
+                event,
+                PollEvent::AutoCancelled { offer, .. } if offer.tradeofferid == tradeofferid,
+            ));
+        // Set before polling starts.
+        let tradeofferid = manager.send_offer(&offer(1)).await.unwrap().tradeofferid;
+        
+        manager.set_cancel_duration(tradeofferid, Some(chrono::Duration::zero())).await.unwrap();
+        
+        let (sender, mut receiver) = manager.start_polling(PollOptions::default()).unwrap();
+        let events = receiver.recv().await.unwrap().unwrap();
+        
+        assert!(is_cancelled(&events, tradeofferid));
+        
+        // Set while polling.
+        let tradeofferid = manager.send_offer(&offer(3)).await.unwrap().tradeofferid;
+        
+        manager.set_cancel_duration(tradeofferid, Some(chrono::Duration::zero())).await.unwrap();
+        sender.send(PollAction::DoPoll(PollType::Auto)).await.unwrap();
+        
+        let events = receiver.recv().await.unwrap().unwrap();
+        
+        assert!(is_cancelled(&events, tradeofferid));
+        assert_eq!(
+            server.state().offers[&tradeofferid].trade_offer_state,
+            TradeOfferState::Canceled,
+        );
+        
+        std::fs::remove_dir_all(&directory).ok();
+    }
+    
+    #[tokio::test]
+    async fn sends_and_confirms_offers() {
+        let steamid = SteamID::try_from(STEAMID).unwrap();
+        let partner = SteamID::try_from(PARTNER).unwrap();
+        let server = MockSteamServer::start(steamid).await.unwrap();
+        let directory = std::env::temp_dir()
+            .join(format!("mock_steam_test_{}", rand::random::<u64>()));
+        
+        {
+            let mut state = server.state();
+            
+            state.add_classinfo(ClassInfo {
+                classid: 101785959,
+                instanceid: Some(11040578),
+                name: "Mann Co. Supply Crate Key".into(),
+                tradable: true,
+                ..Default::default()
+            });
+            state.add_asset(steamid, asset(1));
+        }
+        
+        let manager = TradeOfferManager::builder()
+            .community_url(server.url())
+            .api_url(server.url())
+            .cookies(server.cookies())
+            .identity_secret("AAAAAAAAAAAAAAAAAAAAAAAAAAA=".into())
+            .data_directory(&directory)
+            .build();
+        let offer = NewTradeOffer::builder(partner)
+            .items_to_give(vec![asset(1)])
+            .build();
+        let (sent_offer, outcome) = manager
+            .send_and_confirm(&offer, ConfirmRetryOptions::default())
+            .await
+            .unwrap();
+        
+        assert_eq!(outcome, Some(ConfirmOutcome::Confirmed));
+        assert_eq!(
+            server.state().offers[&sent_offer.tradeofferid].trade_offer_state,
+            TradeOfferState::Active,
+        );
+        
+        std::fs::remove_dir_all(&directory).ok();
+    }
+}
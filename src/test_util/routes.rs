@@ -0,0 +1,604 @@
+use super::MockSteamState;
+use crate::SteamID;
+use crate::api::response::{RawAsset, RawTradeOffer};
+use crate::enums::{ConfirmationType, TradeOfferState, TradeStatus};
+use crate::request::NewTradeOfferItem;
+use crate::response::{ClassInfo, Confirmation};
+use crate::types::{AppId, AssetId, ClassId, ContextId, InstanceId, TradeId, TradeOfferId};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+use axum::Router;
+use axum::extract::{Form, Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Redirect, Response};
+use axum::routing::{get, post};
+use axum::Json;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+pub(super) type SharedState = Arc<Mutex<MockSteamState>>;
+
+/// The page community requests are redirected to when not logged in.
+const LOGIN_PATH: &str = "/login/home/";
+
+/// Creates the router for the community and Web API endpoints.
+pub(crate) fn router(state: SharedState) -> Router {
+    Router::new()
+        .route("/IEconService/GetTradeOffers/v1", get(get_trade_offers))
+        .route("/IEconService/GetTradeOffer/v1", get(get_trade_offer))
+        .route("/IEconService/GetTradeHistory/v1", get(get_trade_history))
+        .route("/ISteamEconomy/GetAssetClassInfo/v1", get(get_asset_classinfo))
+        .route("/tradeoffer/new/send", post(send_offer))
+        .route("/tradeoffer/{tradeofferid}/accept", post(accept_offer))
+        .route("/tradeoffer/{tradeofferid}/decline", post(decline_offer))
+        .route("/tradeoffer/{tradeofferid}/cancel", post(cancel_offer))
+        .route("/inventory/{steamid}/{appid}/{contextid}", get(get_inventory))
+        .route("/mobileconf/getlist", get(get_confirmations))
+        .route("/mobileconf/ajaxop", get(respond_to_confirmation))
+        .route("/mobileconf/multiajaxop", post(respond_to_confirmations))
+        .route("/mobileconf/detailspage/{id}", get(get_confirmation_details))
+        .route("/chat/clientjstoken", get(check_session))
+        .route(LOGIN_PATH, get(login_page))
+        .merge(super::auth::router())
+        .with_state(state)
+}
+
+/// Authentication included with Web API requests.
+#[derive(Deserialize)]
+struct WebApiAuth {
+    #[serde(default)]
+    key: Option<String>,
+    #[serde(default)]
+    access_token: Option<String>,
+}
+
+impl WebApiAuth {
+    fn is_authorized(&self, state: &MockSteamState) -> bool {
+        self.key.is_some() || (self.access_token.is_some() && state.logged_in)
+    }
+}
+
+pub(super) fn lock(state: &SharedState) -> MutexGuard<'_, MockSteamState> {
+    // The state is still usable if a test panicked while holding the lock.
+    state.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+fn unauthorized() -> Response {
+    StatusCode::UNAUTHORIZED.into_response()
+}
+
+fn login_redirect() -> Response {
+    Redirect::to(LOGIN_PATH).into_response()
+}
+
+/// A trade error in the format Steam uses, ending with an EResult code.
+fn trade_error(message: &str, eresult: u32) -> Response {
+    Json(json!({ "strError": format!("{message} ({eresult})") })).into_response()
+}
+
+/// An offer in the format Steam uses. Offers without a message have an empty message.
+fn offer_json(offer: &RawTradeOffer) -> Value {
+    let mut value = json!(offer);
+    
+    value["message"] = json!(offer.message.as_deref().unwrap_or_default());
+    value
+}
+
+/// A confirmation in the format Steam uses. The type is a number.
+fn confirmation_json(confirmation: &Confirmation) -> Value {
+    let conf_type = match confirmation.r#type {
+        ConfirmationType::Generic => 1,
+        ConfirmationType::Trade => 2,
+        ConfirmationType::MarketSell => 3,
+        ConfirmationType::AccountRecovery => 6,
+        ConfirmationType::Unknown(conf_type) => conf_type,
+    };
+    let mut value = json!(confirmation);
+    
+    value["type"] = json!(conf_type);
+    value
+}
+
+fn is_active(state: TradeOfferState) -> bool {
+    matches!(
+        state,
+        TradeOfferState::Active |
+        TradeOfferState::CreatedNeedsConfirmation |
+        TradeOfferState::InEscrow
+    )
+}
+
+/// Collects the descriptions for assets. Classinfos which were not added are left out.
+fn descriptions<I>(
+    state: &MockSteamState,
+    classes: I,
+) -> Vec<ClassInfo>
+where
+    I: IntoIterator<Item = (AppId, ClassId, InstanceId)>,
+{
+    classes
+        .into_iter()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .filter_map(|(appid, classid, instanceid)| {
+            let mut classinfo = state.classinfos.get(&(classid, instanceid))?.clone();
+            
+            classinfo.appid = Some(appid);
+            Some(classinfo)
+        })
+        .collect()
+}
+
+fn asset_classes<'a, I>(assets: I) -> impl Iterator<Item = (AppId, ClassId, InstanceId)> + 'a
+where
+    I: IntoIterator<Item = &'a RawAsset> + 'a,
+{
+    assets
+        .into_iter()
+        .map(|asset| (asset.appid, asset.classid, asset.instanceid))
+}
+
+#[derive(Deserialize)]
+struct GetTradeOffersQuery {
+    #[serde(default)]
+    active_only: bool,
+    #[serde(default)]
+    historical_only: bool,
+    #[serde(default)]
+    get_sent_offers: bool,
+    #[serde(default)]
+    get_received_offers: bool,
+    #[serde(default)]
+    get_descriptions: bool,
+    #[serde(default)]
+    time_historical_cutoff: Option<i64>,
+}
+
+async fn get_trade_offers(
+    State(state): State<SharedState>,
+    Query(auth): Query<WebApiAuth>,
+    Query(query): Query<GetTradeOffersQuery>,
+) -> Response {
+    let state = lock(&state);
+    
+    if !auth.is_authorized(&state) {
+        return unauthorized();
+    }
+    
+    let offers = state.offers
+        .values()
+        .rev()
+        .filter(|offer| if offer.is_our_offer {
+            query.get_sent_offers
+        } else {
+            query.get_received_offers
+        })
+        .filter(|offer| if query.active_only {
+            // Offers updated since the cutoff are included with active offers.
+            is_active(offer.trade_offer_state) || query.time_historical_cutoff
+                .is_some_and(|cutoff| offer.time_updated.timestamp() >= cutoff)
+        } else if query.historical_only {
+            !is_active(offer.trade_offer_state)
+        } else {
+            true
+        })
+        .collect::<Vec<_>>();
+    let (sent, received): (Vec<_>, Vec<_>) = offers
+        .iter()
+        .copied()
+        .partition(|offer| offer.is_our_offer);
+    let sent = sent.into_iter().map(offer_json).collect::<Vec<_>>();
+    let received = received.into_iter().map(offer_json).collect::<Vec<_>>();
+    let mut response = json!({
+        "trade_offers_sent": sent,
+        "trade_offers_received": received,
+        "next_cursor": 0,
+    });
+    
+    if query.get_descriptions {
+        let classes = offers
+            .iter()
+            .flat_map(|offer| offer.items_to_give.iter().chain(&offer.items_to_receive));
+        
+        response["descriptions"] = json!(descriptions(&state, asset_classes(classes)));
+    }
+    
+    Json(json!({ "response": response })).into_response()
+}
+
+#[derive(Deserialize)]
+struct GetTradeOfferQuery {
+    tradeofferid: TradeOfferId,
+}
+
+async fn get_trade_offer(
+    State(state): State<SharedState>,
+    Query(auth): Query<WebApiAuth>,
+    Query(query): Query<GetTradeOfferQuery>,
+) -> Response {
+    let state = lock(&state);
+    
+    if !auth.is_authorized(&state) {
+        return unauthorized();
+    }
+    
+    match state.offers.get(&query.tradeofferid) {
+        Some(offer) => Json(json!({ "response": { "offer": offer_json(offer) } })).into_response(),
+        // Steam responds with an empty object for offers that do not exist.
+        None => Json(json!({ "response": {} })).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct GetTradeHistoryQuery {
+    max_trades: usize,
+    #[serde(default)]
+    start_after_tradeid: Option<TradeId>,
+    #[serde(default)]
+    get_descriptions: bool,
+    #[serde(default)]
+    include_failed: bool,
+}
+
+async fn get_trade_history(
+    State(state): State<SharedState>,
+    Query(auth): Query<WebApiAuth>,
+    Query(query): Query<GetTradeHistoryQuery>,
+) -> Response {
+    let state = lock(&state);
+    
+    if !auth.is_authorized(&state) {
+        return unauthorized();
+    }
+    
+    let trades = state.trades
+        .iter()
+        .filter(|trade| query.include_failed || trade.status != TradeStatus::Failed)
+        .collect::<Vec<_>>();
+    let start = query.start_after_tradeid
+        .and_then(|tradeid| trades.iter().position(|trade| trade.tradeid == tradeid))
+        .map_or(0, |index| index + 1);
+    let page = trades
+        .iter()
+        .skip(start)
+        .take(query.max_trades)
+        .collect::<Vec<_>>();
+    let more = start + page.len() < trades.len();
+    let mut response = json!({
+        "more": more,
+        "total_trades": trades.len(),
+        "trades": page,
+    });
+    
+    if query.get_descriptions {
+        let classes = page
+            .iter()
+            .flat_map(|trade| trade.assets_given.iter().chain(&trade.assets_received))
+            .map(|asset| (asset.appid, asset.classid, asset.instanceid));
+        
+        response["descriptions"] = json!(descriptions(&state, classes));
+    }
+    
+    Json(json!({ "response": response })).into_response()
+}
+
+async fn get_asset_classinfo(
+    State(state): State<SharedState>,
+    Query(auth): Query<WebApiAuth>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    let state = lock(&state);
+    
+    if !auth.is_authorized(&state) {
+        return unauthorized();
+    }
+    
+    let class_count = query.get("class_count")
+        .and_then(|count| count.parse::<usize>().ok())
+        .unwrap_or_default();
+    let mut result = serde_json::Map::new();
+    
+    for i in 0..class_count {
+        let Some(classid) = query.get(&format!("classid{i}"))
+            .and_then(|classid| classid.parse::<ClassId>().ok()) else {
+            continue;
+        };
+        let instanceid = query.get(&format!("instanceid{i}"))
+            .and_then(|instanceid| instanceid.parse::<u64>().ok());
+        let Some(classinfo) = state.classinfos.get(&(classid, instanceid)) else {
+            continue;
+        };
+        let key = match instanceid {
+            Some(instanceid) => format!("{classid}_{instanceid}"),
+            None => classid.to_string(),
+        };
+        
+        result.insert(key, json!(classinfo));
+    }
+    
+    result.insert("success".into(), Value::Bool(true));
+    Json(json!({ "result": result })).into_response()
+}
+
+#[derive(Deserialize)]
+struct SendOfferForm {
+    json_tradeoffer: String,
+    partner: String,
+    #[serde(default)]
+    tradeoffermessage: Option<String>,
+    #[serde(default)]
+    tradeofferid_countered: Option<TradeOfferId>,
+}
+
+#[derive(Deserialize)]
+struct OfferForm {
+    me: OfferFormUser,
+    them: OfferFormUser,
+}
+
+#[derive(Deserialize)]
+struct OfferFormUser {
+    assets: Vec<NewTradeOfferItem>,
+}
+
+async fn send_offer(
+    State(state): State<SharedState>,
+    Form(form): Form<SendOfferForm>,
+) -> Response {
+    let mut state = lock(&state);
+    
+    if !state.logged_in {
+        return login_redirect();
+    }
+    
+    let Some(partner) = form.partner.parse::<u64>().ok()
+        .and_then(|steamid| SteamID::try_from(steamid).ok()) else {
+        return trade_error("There was an error sending your trade offer.", 15);
+    };
+    let Ok(offer) = serde_json::from_str::<OfferForm>(&form.json_tradeoffer) else {
+        return trade_error("There was an error sending your trade offer.", 2);
+    };
+    let find_assets = |steamid: SteamID, items: &[NewTradeOfferItem]| {
+        items
+            .iter()
+            .map(|item| {
+                state.find_asset(steamid, item.appid, item.contextid, item.assetid)
+                    .map(|asset| RawAsset {
+                        amount: item.amount,
+                        ..asset
+                    })
+            })
+            .collect::<Option<Vec<_>>>()
+    };
+    let (Some(items_to_give), Some(items_to_receive)) = (
+        find_assets(state.steamid, &offer.me.assets),
+        find_assets(partner, &offer.them.assets),
+    ) else {
+        // Steam uses this code when items are no longer available.
+        return trade_error("There was an error sending your trade offer.", 26);
+    };
+    
+    if let Some(tradeofferid) = form.tradeofferid_countered {
+        state.set_offer_state(tradeofferid, TradeOfferState::Countered);
+    }
+    
+    let (tradeofferid, needs_confirmation) = state.create_sent_offer(
+        partner,
+        items_to_give,
+        items_to_receive,
+        form.tradeoffermessage,
+    );
+    
+    Json(json!({
+        "tradeofferid": tradeofferid.to_string(),
+        "needs_mobile_confirmation": needs_confirmation,
+        "needs_email_confirmation": false,
+    })).into_response()
+}
+
+async fn accept_offer(
+    State(state): State<SharedState>,
+    Path(tradeofferid): Path<TradeOfferId>,
+) -> Response {
+    let mut state = lock(&state);
+    
+    if !state.logged_in {
+        return login_redirect();
+    }
+    
+    if !state.accept_offer(tradeofferid) {
+        return trade_error("There was an error accepting this trade offer.", 11);
+    }
+    
+    let tradeid = state.offers.get(&tradeofferid)
+        .and_then(|offer| offer.tradeid)
+        .map(|tradeid| tradeid.to_string());
+    
+    Json(json!({ "tradeid": tradeid })).into_response()
+}
+
+async fn decline_offer(
+    State(state): State<SharedState>,
+    Path(tradeofferid): Path<TradeOfferId>,
+) -> Response {
+    end_offer(state, tradeofferid, false)
+}
+
+async fn cancel_offer(
+    State(state): State<SharedState>,
+    Path(tradeofferid): Path<TradeOfferId>,
+) -> Response {
+    end_offer(state, tradeofferid, true)
+}
+
+fn end_offer(
+    state: SharedState,
+    tradeofferid: TradeOfferId,
+    is_our_offer: bool,
+) -> Response {
+    let mut state = lock(&state);
+    
+    if !state.logged_in {
+        return login_redirect();
+    }
+    
+    if !state.end_offer(tradeofferid, is_our_offer) {
+        return trade_error("There was an error modifying this trade offer.", 11);
+    }
+    
+    Json(json!({ "tradeofferid": tradeofferid.to_string() })).into_response()
+}
+
+#[derive(Deserialize)]
+struct GetInventoryQuery {
+    #[serde(default)]
+    count: Option<usize>,
+    #[serde(default)]
+    start_assetid: Option<AssetId>,
+}
+
+async fn get_inventory(
+    State(state): State<SharedState>,
+    Path((steamid, appid, contextid)): Path<(u64, AppId, ContextId)>,
+    Query(query): Query<GetInventoryQuery>,
+) -> Response {
+    let state = lock(&state);
+    let assets = SteamID::try_from(steamid).ok()
+        .and_then(|steamid| state.inventories.get(&(steamid, appid, contextid)))
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let start = query.start_assetid
+        .and_then(|assetid| assets.iter().position(|asset| asset.assetid == assetid))
+        .map_or(0, |index| index + 1);
+    let page = assets
+        .iter()
+        .skip(start)
+        .take(query.count.unwrap_or(assets.len()))
+        .collect::<Vec<_>>();
+    let more_items = start + page.len() < assets.len();
+    let last_assetid = page.last().map(|asset| asset.assetid.to_string());
+    let descriptions = descriptions(&state, asset_classes(page.iter().copied()));
+    
+    Json(json!({
+        "success": 1,
+        "total_inventory_count": assets.len(),
+        "assets": page,
+        "descriptions": descriptions,
+        "more_items": u8::from(more_items),
+        "last_assetid": last_assetid,
+    })).into_response()
+}
+
+fn needs_auth() -> Response {
+    Json(json!({ "success": false, "needauth": true })).into_response()
+}
+
+async fn get_confirmations(
+    State(state): State<SharedState>,
+) -> Response {
+    let state = lock(&state);
+    
+    if !state.logged_in {
+        return needs_auth();
+    }
+    
+    let confirmations = state.confirmations
+        .iter()
+        .map(confirmation_json)
+        .collect::<Vec<_>>();
+    
+    Json(json!({ "success": true, "conf": confirmations })).into_response()
+}
+
+#[derive(Deserialize)]
+struct ConfirmationOperation {
+    op: String,
+    cid: u64,
+    ck: u64,
+}
+
+async fn respond_to_confirmation(
+    State(state): State<SharedState>,
+    Query(query): Query<ConfirmationOperation>,
+) -> Response {
+    let mut state = lock(&state);
+    
+    if !state.logged_in {
+        return needs_auth();
+    }
+    
+    let success = state.respond_to_confirmation(query.cid, query.ck, query.op == "allow");
+    
+    Json(json!({ "success": success })).into_response()
+}
+
+async fn respond_to_confirmations(
+    State(state): State<SharedState>,
+    Form(form): Form<Vec<(String, String)>>,
+) -> Response {
+    let mut state = lock(&state);
+    
+    if !state.logged_in {
+        return needs_auth();
+    }
+    
+    let values = |name: &str| {
+        form
+            .iter()
+            .filter(|(key, _value)| key == name)
+            .filter_map(|(_key, value)| value.parse::<u64>().ok())
+            .collect::<Vec<_>>()
+    };
+    let accept = form.iter().any(|(key, value)| key == "op" && value == "allow");
+    let ids = values("cid[]");
+    let nonces = values("ck[]");
+    let mut success = !ids.is_empty() && ids.len() == nonces.len();
+    
+    for (id, nonce) in ids.into_iter().zip(nonces) {
+        success &= state.respond_to_confirmation(id, nonce, accept);
+    }
+    
+    Json(json!({ "success": success })).into_response()
+}
+
+async fn get_confirmation_details(
+    State(state): State<SharedState>,
+    Path(id): Path<u64>,
+) -> Response {
+    let state = lock(&state);
+    
+    if !state.logged_in {
+        return login_redirect();
+    }
+    
+    let Some(confirmation) = state.confirmations
+        .iter()
+        .find(|confirmation| confirmation.id == id) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let accountid = state.offers.get(&confirmation.creator_id)
+        .map(|offer| offer.accountid_other)
+        .unwrap_or_default();
+    
+    Html(format!(
+        concat!(
+            r#"<div class="tradeoffer" id="tradeofferid_{}">"#,
+            r#"<div class="tradeoffer_partner"><div data-miniprofile="{}"></div></div>"#,
+            r#"</div>"#,
+        ),
+        confirmation.creator_id,
+        accountid,
+    )).into_response()
+}
+
+async fn check_session(
+    State(state): State<SharedState>,
+) -> Response {
+    let logged_in = lock(&state).logged_in;
+    
+    Json(json!({ "logged_in": logged_in })).into_response()
+}
+
+async fn login_page() -> Html<&'static str> {
+    Html(r#"<h1>Sign In</h1><script>g_steamID = false;</script>"#)
+}
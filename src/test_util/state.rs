@@ -0,0 +1,367 @@
+use crate::SteamID;
+use crate::api::response::{RawAsset, RawTrade, RawTradeAsset, RawTradeOffer};
+use crate::enums::{ConfirmationMethod, TradeOfferState, TradeStatus};
+use crate::response::{ClassInfo, Confirmation};
+use crate::{fixtures, time};
+use crate::types::{AppId, AssetId, ClassId, ContextId, InstanceId, TradeOfferId};
+use std::collections::{BTreeMap, HashMap};
+use chrono::Duration;
+use rsa::RsaPrivateKey;
+use rsa::rand_core::OsRng;
+use steamid_ng::{AccountType, Instance, InstanceFlags, InstanceType, Universe};
+
+/// The first ID given to offers, trades and confirmations created by the server.
+const FIRST_ID: u64 = 1000;
+/// The number of days before offers expire.
+const OFFER_EXPIRATION_DAYS: i64 = 14;
+/// The size of the key passwords are encrypted with. Smaller than Steam's to keep tests fast.
+const RSA_KEY_BITS: usize = 512;
+
+/// The state of a [`MockSteamServer`](super::MockSteamServer). Offers are from the perspective of
+/// the account the server acts for.
+///
+/// Fields can be modified directly to script a scenario. The methods cover common changes and
+/// keep related state consistent, e.g. accepting an offer also adds a trade to the history.
+#[derive(Debug)]
+pub struct MockSteamState {
+    /// The account the server acts for.
+    pub steamid: SteamID,
+    /// Whether requests are treated as logged in. When `false`, community requests redirect to
+    /// the login page and Web API requests respond with `401 Unauthorized`.
+    pub logged_in: bool,
+    /// Whether offers we send which give items need mobile confirmation. `true` by default.
+    pub require_confirmation: bool,
+    /// Trade offers by ID.
+    pub offers: BTreeMap<TradeOfferId, RawTradeOffer>,
+    /// Classinfos by class ID and instance ID.
+    pub classinfos: HashMap<(ClassId, InstanceId), ClassInfo>,
+    /// Inventories by owner, app ID and context ID.
+    pub inventories: HashMap<(SteamID, AppId, ContextId), Vec<RawAsset>>,
+    /// The trade history, newest first.
+    pub trades: Vec<RawTrade>,
+    /// Pending mobile confirmations.
+    pub confirmations: Vec<Confirmation>,
+    /// The account name for logging in using [`AuthClient`](crate::auth::AuthClient).
+    pub account_name: String,
+    /// The password for logging in.
+    pub password: String,
+    /// The shared secret Steam Guard codes are generated from. When set, logging in requires a
+    /// code generated from it. `None` by default.
+    pub shared_secret: Option<String>,
+    /// The key passwords are encrypted with when logging in. Generated when first requested.
+    rsa_key: Option<RsaPrivateKey>,
+    /// Whether each auth session has been approved, by client ID.
+    pub(crate) auth_sessions: HashMap<u64, bool>,
+    next_id: u64,
+}
+
+impl MockSteamState {
+    /// Creates an empty state for `steamid`.
+    pub(crate) fn new(steamid: SteamID) -> Self {
+        Self {
+            steamid,
+            logged_in: true,
+            require_confirmation: true,
+            offers: BTreeMap::new(),
+            classinfos: HashMap::new(),
+            inventories: HashMap::new(),
+            trades: Vec::new(),
+            confirmations: Vec::new(),
+            account_name: "mockuser".into(),
+            password: "mockpassword".into(),
+            shared_secret: None,
+            rsa_key: None,
+            auth_sessions: HashMap::new(),
+            next_id: FIRST_ID,
+        }
+    }
+    
+    /// Adds a classinfo. Assets are described using the classinfo with the same class ID and
+    /// instance ID.
+    pub fn add_classinfo(
+        &mut self,
+        classinfo: ClassInfo,
+    ) {
+        self.classinfos.insert((classinfo.classid, classinfo.instanceid), classinfo);
+    }
+    
+    /// Adds an asset to the inventory of `steamid`. Assets must be in an inventory to be added to
+    /// offers we send.
+    pub fn add_asset(
+        &mut self,
+        steamid: SteamID,
+        asset: RawAsset,
+    ) {
+        self.inventories
+            .entry((steamid, asset.appid, asset.contextid))
+            .or_default()
+            .push(asset);
+    }
+    
+    /// Adds an active offer sent to us by `partner`. Returns the ID of the offer.
+    pub fn add_received_offer(
+        &mut self,
+        partner: SteamID,
+        items_to_give: Vec<RawAsset>,
+        items_to_receive: Vec<RawAsset>,
+    ) -> TradeOfferId {
+        let tradeofferid = self.next_id();
+        let offer = new_offer(
+            tradeofferid,
+            partner,
+            items_to_give,
+            items_to_receive,
+            false,
+            TradeOfferState::Active,
+        );
+        
+        self.offers.insert(tradeofferid, offer);
+        tradeofferid
+    }
+    
+    /// Sets the state of an offer, e.g. to simulate our partner accepting an offer we sent.
+    /// Offers changed to [`TradeOfferState::Accepted`] are given a trade ID and added to the trade
+    /// history. Returns `false` if the offer does not exist.
+    pub fn set_offer_state(
+        &mut self,
+        tradeofferid: TradeOfferId,
+        state: TradeOfferState,
+    ) -> bool {
+        if state == TradeOfferState::Accepted {
+            return self.complete_offer(tradeofferid);
+        }
+        
+        let Some(offer) = self.offers.get_mut(&tradeofferid) else {
+            return false;
+        };
+        
+        offer.trade_offer_state = state;
+        offer.time_updated = time::get_server_time_now();
+        true
+    }
+    
+    /// Gets the pending confirmation for an offer.
+    pub fn confirmation_for_offer(
+        &self,
+        tradeofferid: TradeOfferId,
+    ) -> Option<&Confirmation> {
+        self.confirmations
+            .iter()
+            .find(|confirmation| confirmation.creator_id == tradeofferid)
+    }
+    
+    /// Finds an asset in the inventory of `steamid`.
+    pub(crate) fn find_asset(
+        &self,
+        steamid: SteamID,
+        appid: AppId,
+        contextid: ContextId,
+        assetid: AssetId,
+    ) -> Option<RawAsset> {
+        self.inventories.get(&(steamid, appid, contextid))?
+            .iter()
+            .find(|asset| asset.assetid == assetid)
+            .copied()
+    }
+    
+    /// Creates an offer sent by us. A confirmation is added if the offer needs one. Returns the
+    /// ID of the offer and whether it needs confirmation.
+    pub(crate) fn create_sent_offer(
+        &mut self,
+        partner: SteamID,
+        items_to_give: Vec<RawAsset>,
+        items_to_receive: Vec<RawAsset>,
+        message: Option<String>,
+    ) -> (TradeOfferId, bool) {
+        let tradeofferid = self.next_id();
+        let needs_confirmation = self.require_confirmation && !items_to_give.is_empty();
+        let state = if needs_confirmation {
+            TradeOfferState::CreatedNeedsConfirmation
+        } else {
+            TradeOfferState::Active
+        };
+        let mut offer = new_offer(
+            tradeofferid,
+            partner,
+            items_to_give,
+            items_to_receive,
+            true,
+            state,
+        );
+        
+        offer.message = message.filter(|message| !message.is_empty());
+        
+        if needs_confirmation {
+            offer.confirmation_method = ConfirmationMethod::MobileApp;
+            
+            let id = self.next_id();
+            let confirmation = Confirmation {
+                creation_time: offer.time_created,
+                nonce: self.next_id(),
+                accept: "Send Offer".into(),
+                multi: true,
+                headline: u64::from(partner).to_string(),
+                summary: vec![format!("You will give {} items", offer.items_to_give.len())],
+                ..fixtures::confirmation(id, tradeofferid)
+            };
+            
+            self.confirmations.push(confirmation);
+        }
+        
+        self.offers.insert(tradeofferid, offer);
+        (tradeofferid, needs_confirmation)
+    }
+    
+    /// Accepts an offer sent to us. Returns `false` if the offer cannot be accepted.
+    pub(crate) fn accept_offer(
+        &mut self,
+        tradeofferid: TradeOfferId,
+    ) -> bool {
+        let can_accept = self.offers.get(&tradeofferid)
+            .is_some_and(|offer| {
+                !offer.is_our_offer && offer.trade_offer_state == TradeOfferState::Active
+            });
+        
+        can_accept && self.complete_offer(tradeofferid)
+    }
+    
+    /// Declines an offer sent to us or cancels an offer we sent. Returns `false` if the offer
+    /// cannot be changed.
+    pub(crate) fn end_offer(
+        &mut self,
+        tradeofferid: TradeOfferId,
+        is_our_offer: bool,
+    ) -> bool {
+        let Some(offer) = self.offers.get_mut(&tradeofferid)
+            .filter(|offer| offer.is_our_offer == is_our_offer) else {
+            return false;
+        };
+        let state = match (offer.trade_offer_state, is_our_offer) {
+            (TradeOfferState::Active, false) => TradeOfferState::Declined,
+            (TradeOfferState::Active, true) |
+            (TradeOfferState::CreatedNeedsConfirmation, true) => TradeOfferState::Canceled,
+            _ => return false,
+        };
+        
+        offer.trade_offer_state = state;
+        offer.time_updated = time::get_server_time_now();
+        self.confirmations.retain(|confirmation| confirmation.creator_id != tradeofferid);
+        true
+    }
+    
+    /// Accepts or cancels a confirmation. The offer the confirmation is for is updated. Returns
+    /// `false` if there is no confirmation matching `id` and `nonce`.
+    pub(crate) fn respond_to_confirmation(
+        &mut self,
+        id: u64,
+        nonce: u64,
+        accept: bool,
+    ) -> bool {
+        let Some(index) = self.confirmations
+            .iter()
+            .position(|confirmation| confirmation.id == id && confirmation.nonce == nonce) else {
+            return false;
+        };
+        let confirmation = self.confirmations.remove(index);
+        let offer = self.offers.get_mut(&confirmation.creator_id)
+            .filter(|offer| offer.trade_offer_state == TradeOfferState::CreatedNeedsConfirmation);
+        
+        if let Some(offer) = offer {
+            offer.trade_offer_state = if accept {
+                TradeOfferState::Active
+            } else {
+                TradeOfferState::CanceledBySecondFactor
+            };
+            offer.time_updated = time::get_server_time_now();
+        }
+        
+        true
+    }
+    
+    /// Marks an offer as accepted and adds its trade to the history.
+    fn complete_offer(
+        &mut self,
+        tradeofferid: TradeOfferId,
+    ) -> bool {
+        let tradeid = self.next_id() as u128;
+        let Some(offer) = self.offers.get_mut(&tradeofferid) else {
+            return false;
+        };
+        let now = time::get_server_time_now();
+        
+        offer.trade_offer_state = TradeOfferState::Accepted;
+        offer.tradeid = Some(tradeid);
+        offer.time_updated = now;
+        
+        let steamid_other = SteamID::new(
+            offer.accountid_other,
+            Instance::new(InstanceType::Desktop, InstanceFlags::None),
+            AccountType::Individual,
+            Universe::Public,
+        );
+        let trade = RawTrade {
+            tradeid,
+            steamid_other,
+            time_init: now,
+            status: TradeStatus::Complete,
+            assets_given: offer.items_to_give.iter().map(trade_asset).collect(),
+            assets_received: offer.items_to_receive.iter().map(trade_asset).collect(),
+            rollback_trade: None,
+        };
+        
+        self.trades.insert(0, trade);
+        true
+    }
+    
+    /// Gets the key passwords are encrypted with, generating it if needed.
+    pub(crate) fn rsa_key(&mut self) -> &RsaPrivateKey {
+        self.rsa_key.get_or_insert_with(|| {
+            // unwrap is safe because the key size is supported
+            RsaPrivateKey::new(&mut OsRng, RSA_KEY_BITS).unwrap()
+        })
+    }
+    
+    pub(crate) fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        
+        self.next_id += 1;
+        id
+    }
+}
+
+/// Creates a new offer with `partner`.
+fn new_offer(
+    tradeofferid: TradeOfferId,
+    partner: SteamID,
+    items_to_give: Vec<RawAsset>,
+    items_to_receive: Vec<RawAsset>,
+    is_our_offer: bool,
+    trade_offer_state: TradeOfferState,
+) -> RawTradeOffer {
+    let offer = fixtures::raw_offer(tradeofferid, trade_offer_state);
+    // unwrap is safe because the value of OFFER_EXPIRATION_DAYS is in range
+    let expiration = Duration::try_days(OFFER_EXPIRATION_DAYS).unwrap();
+    
+    RawTradeOffer {
+        accountid_other: partner.account_id(),
+        items_to_receive,
+        items_to_give,
+        is_our_offer,
+        expiration_time: offer.time_created + expiration,
+        ..offer
+    }
+}
+
+/// Converts an asset in an offer to an asset in a trade. Traded assets keep their IDs.
+fn trade_asset(asset: &RawAsset) -> RawTradeAsset {
+    RawTradeAsset {
+        appid: asset.appid,
+        contextid: asset.contextid,
+        assetid: asset.assetid,
+        amount: asset.amount,
+        classid: asset.classid,
+        instanceid: asset.instanceid,
+        new_contextid: Some(asset.contextid),
+        new_assetid: Some(asset.assetid),
+    }
+}
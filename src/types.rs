@@ -18,6 +18,11 @@ pub type TradeOfferId = u64;
 /// An ID of a trade.
 pub type TradeId = u128;
 
+/// Identifies the [`ClassInfo`] for an asset.
+pub type ClassInfoClass = (AppId, ClassId, InstanceId);
+/// A map of [`ClassInfo`] data keyed by the class it describes.
+pub type ClassInfoMap = HashMap<ClassInfoClass, Arc<ClassInfo>>;
+
 pub use crate::time::ServerTime;
 
 // Types internally used by the crate.
@@ -26,8 +31,6 @@ use std::sync::Arc;
 use std::collections::HashMap;
 
 pub(crate) type HttpClient = reqwest_middleware::ClientWithMiddleware;
-pub(crate) type ClassInfoClass = (AppId, ClassId, InstanceId);
-pub(crate) type ClassInfoMap = HashMap<ClassInfoClass, Arc<ClassInfo>>;
 pub(crate) type ClassInfoAppClass = (ClassId, InstanceId);
 pub(crate) type ClassInfoAppMap = HashMap<ClassInfoAppClass, Arc<ClassInfo>>;
 pub(crate) type AssetPropertiesMap = HashMap<(AppId, ContextId, AssetId), Vec<AssetProperty>>;